@@ -8,6 +8,9 @@ use syn::parse::{Parse, ParseStream};
 pub struct Config {
     pub ignore_args: HashSet<syn::Ident>,
     pub use_thread: bool,
+    pub field: Option<syn::Ident>,
+    pub dedup: bool,
+    pub shards: Option<usize>,
 }
 
 struct IgnoreArgsAttrib {
@@ -17,6 +20,9 @@ struct IgnoreArgsAttrib {
 enum ConfigAttrib {
     IgnoreArgs(IgnoreArgsAttrib),
     UseTread,
+    Field(syn::Ident),
+    Dedup,
+    Shards(usize),
 }
 
 const CONFIG_ATTRIBUTE_NAME: &'static str = "cache_cfg";
@@ -48,6 +54,9 @@ impl Config {
             match parsed_attrib {
                 ConfigAttrib::IgnoreArgs(val) => config.ignore_args = val.ignore_args,
                 ConfigAttrib::UseTread => config.use_thread = true,
+                ConfigAttrib::Field(val) => config.field = Some(val),
+                ConfigAttrib::Dedup => config.dedup = true,
+                ConfigAttrib::Shards(val) => config.shards = Some(val),
             }
         }
 
@@ -60,6 +69,9 @@ impl Default for Config {
         Config {
             ignore_args: HashSet::new(),
             use_thread: false,
+            field: None,
+            dedup: false,
+            shards: None,
         }
     }
 }
@@ -73,6 +85,19 @@ impl Parse for ConfigAttrib {
         match &name.to_string()[..] {
             "ignore_args" => Ok(ConfigAttrib::IgnoreArgs(content.parse::<IgnoreArgsAttrib>()?)),
             "thread" => Ok(ConfigAttrib::UseTread),
+            "dedup" => Ok(ConfigAttrib::Dedup),
+            "shard" => {
+                content.parse::<Token![=]>()?;
+                let shards = content.parse::<syn::LitInt>()?;
+                match shards.base10_parse::<usize>()? {
+                    0 => Err(syn::parse::Error::new(shards.span(), "shard count must be greater than 0")),
+                    val => Ok(ConfigAttrib::Shards(val)),
+                }
+            }
+            "field" => {
+                content.parse::<Token![=]>()?;
+                Ok(ConfigAttrib::Field(content.parse::<syn::Ident>()?))
+            }
             _ => Err(syn::parse::Error::new(
                 name.span(), format!("unrecognized config option '{}'", name.to_string())
             ))
@@ -14,18 +14,25 @@ use syn::parse::ParseStream;
 use syn::parse_macro_input;
 
 struct Attr {
-    cache_type: syn::Type,
-    cache_creation_expr: syn::Expr,
+    cache_type: Option<syn::Type>,
+    cache_creation_expr: Option<syn::Expr>,
 }
 
 impl Parse for Attr {
     fn parse(input: ParseStream) -> syn::parse::Result<Self> {
+        // 缓存存放于结构体字段中时, 可省略缓存类型及创建表达式
+        if input.is_empty() {
+            return Ok(Attr {
+                cache_type: None,
+                cache_creation_expr: None,
+            });
+        }
         let cache_type: syn::Type = input.parse()?;
         input.parse::<Token![:]>()?;
         let cache_creation_expr: syn::Expr = input.parse()?;
         Ok(Attr {
-            cache_type,
-            cache_creation_expr,
+            cache_type: Some(cache_type),
+            cache_creation_expr: Some(cache_creation_expr),
         })
     }
 }
@@ -58,59 +65,233 @@ fn algorithm_cache_impl(attr: Attr, item: TokenStream) -> syn::parse::Result<Tok
     original_fn.sig.ident = syn::Ident::new(&new_name[..], original_fn.sig.ident.span());
     let (call_args, types, cache_args) = get_args_and_types(&original_fn, &macro_config)?;
     let cloned_args = make_cloned_args_tuple(&cache_args);
-    let fn_path = path_from_ident(original_fn.sig.ident.clone());
-    let fn_call = syn::ExprCall {
-        attrs: Vec::new(),
-        paren_token: syn::token::Paren::default(),
-        args: call_args,
-        func: Box::new(fn_path)
-    };
+    let mut fn_call = make_inner_fn_call(&original_fn, call_args);
+    if original_fn.sig.asyncness.is_some() {
+        fn_call = parse_quote! { #fn_call.await };
+    } else if macro_config.dedup {
+        return Err(syn::Error::new_spanned(&original_fn.sig, "`cache_cfg(dedup)` is only supported for async functions"));
+    }
 
-    let tuple_type = syn::TypeTuple {
-        paren_token: syn::token::Paren::default(),
-        elems: types,
+    let out = if let Some(field) = &macro_config.field {
+        if macro_config.dedup || macro_config.shards.is_some() {
+            return Err(syn::Error::new_spanned(&original_fn.sig, "`cache_cfg(dedup)` and `cache_cfg(shard = ...)` are unsupported with `cache_cfg(field = ...)`"));
+        }
+        *new_fn.block = build_field_cache_body(field, &cloned_args, &fn_call, &macro_config);
+        quote! {
+            #original_fn
+            #new_fn
+        }
+    } else {
+        let (cache_type, cache_creation_expr) = match (&attr.cache_type, &attr.cache_creation_expr) {
+            (Some(cache_type), Some(cache_creation_expr)) => (cache_type, cache_creation_expr),
+            _ => return Err(syn::Error::new_spanned(&original_fn.sig, "cache type is required unless `cache_cfg(field = ...)` is set")),
+        };
+        let tuple_type = syn::TypeTuple {
+            paren_token: syn::token::Paren::default(),
+            elems: types,
+        };
+        let cache_type_with_generics: syn::Type = parse_quote! {
+            #cache_type<#tuple_type, #return_type, algorithm::DefaultHasher>
+        };
+        let storage = match macro_config.shards {
+            Some(shards) => CacheStorage::Shards(shards),
+            None if macro_config.use_thread || original_fn.sig.asyncness.is_some() => CacheStorage::Mutex,
+            None => CacheStorage::ThreadLocal,
+        };
+        let cache_ident = syn::Ident::new(&format!("__ALGORITHM_CACHE_{}", new_fn.sig.ident.to_string().to_uppercase()),
+            new_fn.sig.ident.span());
+        let cache_static = build_cache_static(&cache_ident, &cache_type_with_generics, cache_creation_expr, storage);
+        let companions = build_cache_companions(&new_fn, &cache_ident, &cache_type_with_generics, &return_type,
+            &cloned_args, get_key_inputs(&original_fn, &macro_config), storage);
+        *new_fn.block = build_cache_body(&cache_ident, &tuple_type, &cloned_args,
+            &fn_call, original_fn.sig.asyncness.is_some(), &macro_config);
+        quote! {
+            #cache_static
+            #original_fn
+            #new_fn
+            #companions
+        }
     };
 
-    let cache_type = &attr.cache_type;
-    let cache_type_with_generics: syn::Type = parse_quote! {
-        #cache_type<#tuple_type, #return_type, algorithm::DefaultHasher>
+    Ok(out.into())
+}
+
+// Where the static cache of a free function lives.
+#[derive(Clone, Copy)]
+enum CacheStorage {
+    ThreadLocal,
+    Mutex,
+    Shards(usize),
+}
+
+// Build the static which holds the cache, outside of the function so the companion functions can reach it.
+fn build_cache_static(cache_ident: &syn::Ident, full_cache_type: &syn::Type, cache_new: &syn::Expr,
+                      storage: CacheStorage) -> proc_macro2::TokenStream
+{
+    match storage {
+        CacheStorage::ThreadLocal => quote! {
+            std::thread_local!(
+                static #cache_ident: std::cell::RefCell<#full_cache_type> =
+                    std::cell::RefCell::new(#cache_new);
+            );
+        },
+        CacheStorage::Mutex => quote! {
+            lazy_static::lazy_static! {
+                static ref #cache_ident: std::sync::Mutex<#full_cache_type> =
+                    std::sync::Mutex::new(#cache_new);
+            }
+        },
+        CacheStorage::Shards(shards) => quote! {
+            lazy_static::lazy_static! {
+                static ref #cache_ident: Vec<std::sync::Mutex<#full_cache_type>> =
+                    (0..#shards).map(|_| std::sync::Mutex::new(#cache_new)).collect();
+            }
+        },
+    }
+}
+
+// Select the mutex guarding the cache of `cloned_args`, by the hash of the key when the cache is sharded.
+fn build_select_shard(cache_ident: &syn::Ident, shards: Option<usize>) -> syn::Stmt {
+    if let Some(shards) = shards {
+        parse_quote! {
+            let cache_shard = &#cache_ident[{
+                use std::hash::{Hash, Hasher};
+                let mut hasher = std::collections::hash_map::DefaultHasher::new();
+                cloned_args.hash(&mut hasher);
+                hasher.finish() as usize % #shards
+            }];
+        }
+    } else {
+        parse_quote! {
+            let cache_shard = &*#cache_ident;
+        }
+    }
+}
+
+// Build the `<name>_cache_with`, `<name>_cache_clear`, `<name>_cache_len` and `<name>_cache_remove` functions.
+// With thread-local storage they only see the cache of the calling thread, with a sharded cache
+// `<name>_cache_with` visits every shard. Poisoned mutexes are skipped like in the caching function,
+// except for `<name>_cache_with` on a single mutex, which recovers the guard so it can always return.
+fn build_cache_companions(new_fn: &syn::ItemFn, cache_ident: &syn::Ident, full_cache_type: &syn::Type,
+                          return_type: &syn::Type, cloned_args: &syn::ExprTuple,
+                          key_inputs: Punctuated<syn::FnArg, Token![,]>, storage: CacheStorage) -> proc_macro2::TokenStream
+{
+    let vis = &new_fn.vis;
+    let name = new_fn.sig.ident.to_string();
+    let with_ident = syn::Ident::new(&format!("{}_cache_with", name), new_fn.sig.ident.span());
+    let clear_ident = syn::Ident::new(&format!("{}_cache_clear", name), new_fn.sig.ident.span());
+    let len_ident = syn::Ident::new(&format!("{}_cache_len", name), new_fn.sig.ident.span());
+    let remove_ident = syn::Ident::new(&format!("{}_cache_remove", name), new_fn.sig.ident.span());
+    let with_doc = format!("对`{}`的缓存进行操作, 在闭包中不可再次访问该缓存", name);
+    let clear_doc = format!("清空`{}`的缓存", name);
+    let len_doc = format!("`{}`已缓存的结果数量", name);
+    let remove_doc = format!("移除`{}`指定参数的缓存结果", name);
+
+    let shards = match storage {
+        CacheStorage::ThreadLocal => return quote! {
+            #[doc = #with_doc]
+            #[allow(dead_code)]
+            #vis fn #with_ident<R, F: FnOnce(&mut #full_cache_type) -> R>(f: F) -> R {
+                #cache_ident.with(|c| f(&mut c.borrow_mut()))
+            }
+
+            #[doc = #clear_doc]
+            #[allow(dead_code)]
+            #vis fn #clear_ident() {
+                #with_ident(|c| c.clear())
+            }
+
+            #[doc = #len_doc]
+            #[allow(dead_code)]
+            #vis fn #len_ident() -> usize {
+                #with_ident(|c| c.len())
+            }
+
+            #[doc = #remove_doc]
+            #[allow(dead_code)]
+            #vis fn #remove_ident(#key_inputs) -> Option<#return_type> {
+                let cloned_args = #cloned_args;
+                #with_ident(|c| c.remove(&cloned_args).map(|(_, v)| v))
+            }
+        },
+        CacheStorage::Mutex => None,
+        CacheStorage::Shards(shards) => Some(shards),
     };
-    let lru_body = build_cache_body(&cache_type_with_generics, &attr.cache_creation_expr, &cloned_args,
-        &fn_call, &macro_config);
 
-    new_fn.block = Box::new(lru_body);
-    let out = quote! {
-        #original_fn
-        #new_fn
+    let (with_fn, all_shards) = if shards.is_some() {
+        (quote! {
+            #[doc = #with_doc]
+            #[allow(dead_code)]
+            #vis fn #with_ident<F: FnMut(&mut #full_cache_type)>(mut f: F) {
+                for shard in #cache_ident.iter() {
+                    if let Ok(mut c) = shard.lock() {
+                        f(&mut c);
+                    }
+                }
+            }
+        }, quote! { #cache_ident.iter() })
+    } else {
+        (quote! {
+            #[doc = #with_doc]
+            #[allow(dead_code)]
+            #vis fn #with_ident<R, F: FnOnce(&mut #full_cache_type) -> R>(f: F) -> R {
+                f(&mut #cache_ident.lock().unwrap_or_else(std::sync::PoisonError::into_inner))
+            }
+        }, quote! { std::iter::once(&*#cache_ident) })
     };
-    Ok(out.into())
+    let select_shard = build_select_shard(cache_ident, shards);
+
+    quote! {
+        #with_fn
+
+        #[doc = #clear_doc]
+        #[allow(dead_code)]
+        #vis fn #clear_ident() {
+            for shard in #all_shards {
+                if let Ok(mut c) = shard.lock() {
+                    c.clear();
+                }
+            }
+        }
+
+        #[doc = #len_doc]
+        #[allow(dead_code)]
+        #vis fn #len_ident() -> usize {
+            #all_shards.filter_map(|shard| shard.lock().ok().map(|c| c.len())).sum()
+        }
+
+        #[doc = #remove_doc]
+        #[allow(dead_code)]
+        #vis fn #remove_ident(#key_inputs) -> Option<#return_type> {
+            let cloned_args = #cloned_args;
+            #select_shard
+            let mut c = cache_shard.lock().ok()?;
+            c.remove(&cloned_args).map(|(_, v)| v)
+        }
+    }
 }
 
 // Build the body of the caching function. What is constructed depends on the config value.
-fn build_cache_body(full_cache_type: &syn::Type, cache_new: &syn::Expr,
-                    cloned_args: &syn::ExprTuple, inner_fn_call: &syn::ExprCall,
-                    config: &config::Config) -> syn::Block
+fn build_cache_body(cache_ident: &syn::Ident, key_type: &syn::TypeTuple,
+                    cloned_args: &syn::ExprTuple, inner_fn_call: &syn::Expr,
+                    is_async: bool, config: &config::Config) -> syn::Block
 {
-    if config.use_thread {
-        build_mutex_cache_body(full_cache_type, cache_new, cloned_args, inner_fn_call)
+    if is_async {
+        build_async_cache_body(cache_ident, key_type, cloned_args, inner_fn_call, config)
+    } else if config.use_thread || config.shards.is_some() {
+        build_mutex_cache_body(cache_ident, cloned_args, inner_fn_call, config.shards)
     } else {
-        build_tls_cache_body(full_cache_type, cache_new, cloned_args, inner_fn_call)
+        build_tls_cache_body(cache_ident, cloned_args, inner_fn_call)
     }
 }
 
 // Build the body of the caching function which puts the cache in thread-local storage.
-fn build_tls_cache_body(full_cache_type: &syn::Type, cache_new: &syn::Expr,
-                     cloned_args: &syn::ExprTuple, inner_fn_call: &syn::ExprCall) -> syn::Block
+fn build_tls_cache_body(cache_ident: &syn::Ident, cloned_args: &syn::ExprTuple,
+                        inner_fn_call: &syn::Expr) -> syn::Block
 {
     parse_quote! {
         {
-            use std::cell::RefCell;
-            use std::thread_local;
-            thread_local!(
-                static cache: RefCell<#full_cache_type> =
-                    RefCell::new(#cache_new);
-            );
-            cache.with(|c| {
+            #cache_ident.with(|c| {
                 let mut cache_ref = c.borrow_mut();
                 let cloned_args = #cloned_args;
 
@@ -132,33 +313,149 @@ fn build_tls_cache_body(full_cache_type: &syn::Type, cache_new: &syn::Expr,
 }
 
 // Build the body of the caching function which guards the static cache with a mutex.
-fn build_mutex_cache_body(full_cache_type: &syn::Type, cache_new: &syn::Expr,
-                     cloned_args: &syn::ExprTuple, inner_fn_call: &syn::ExprCall) -> syn::Block
+// A poisoned mutex is bypassed and the inner function is called without caching.
+fn build_mutex_cache_body(cache_ident: &syn::Ident, cloned_args: &syn::ExprTuple,
+                          inner_fn_call: &syn::Expr, shards: Option<usize>) -> syn::Block
+{
+    let select_shard = build_select_shard(cache_ident, shards);
+    parse_quote! {
+        {
+            let cloned_args = #cloned_args;
+            #select_shard
+
+            // the guard is released at the end of the block to allow potentially recursive call
+            if let Ok(mut cache_unlocked) = cache_shard.lock() {
+                if let Some(stored_result) = cache_unlocked.get_mut(&cloned_args) {
+                    return stored_result.clone();
+                }
+            }
+
+            let ret = #inner_fn_call;
+            if let Ok(mut cache_unlocked) = cache_shard.lock() {
+                cache_unlocked.insert(cloned_args, ret.clone());
+            }
+            ret
+        }
+    }
+}
+
+// Build the body of an async caching function. The cache is always guarded by a static mutex
+// because the task may resume on another thread, and the lock is never held across `.await`.
+// With `dedup`, callers with the same arguments wait for the in-flight call instead of running it again.
+fn build_async_cache_body(cache_ident: &syn::Ident, key_type: &syn::TypeTuple, cloned_args: &syn::ExprTuple,
+                          inner_fn_call: &syn::Expr, config: &config::Config) -> syn::Block
 {
+    let select_shard = build_select_shard(cache_ident, config.shards);
+    if !config.dedup {
+        return parse_quote! {
+            {
+                let cloned_args = #cloned_args;
+                #select_shard
+                if let Ok(mut cache_unlocked) = cache_shard.lock() {
+                    if let Some(stored_result) = cache_unlocked.get_mut(&cloned_args) {
+                        return stored_result.clone();
+                    }
+                }
+
+                let ret = #inner_fn_call;
+                if let Ok(mut cache_unlocked) = cache_shard.lock() {
+                    cache_unlocked.insert(cloned_args, ret.clone());
+                }
+                ret
+            }
+        };
+    }
+
     parse_quote! {
         {
             use lazy_static::lazy_static;
-            use std::sync::Mutex;
+            use std::collections::HashMap;
+            use std::sync::{Mutex, PoisonError};
+            use std::task::{Poll, Waker};
 
             lazy_static! {
-                static ref cache: Mutex<#full_cache_type> =
-                    Mutex::new(#cache_new);
+                static ref in_flight: Mutex<HashMap<#key_type, Vec<Waker>>> =
+                    Mutex::new(HashMap::new());
+            }
+
+            // Wakes the waiters even if the running call is cancelled.
+            struct InFlightGuard(#key_type);
+            impl Drop for InFlightGuard {
+                fn drop(&mut self) {
+                    if let Some(wakers) = in_flight.lock().unwrap_or_else(PoisonError::into_inner).remove(&self.0) {
+                        for waker in wakers {
+                            waker.wake();
+                        }
+                    }
+                }
             }
 
             let cloned_args = #cloned_args;
+            #select_shard
+            loop {
+                if let Ok(mut cache_unlocked) = cache_shard.lock() {
+                    if let Some(stored_result) = cache_unlocked.get_mut(&cloned_args) {
+                        return stored_result.clone();
+                    }
+                }
 
-            let mut cache_unlocked = cache.lock().unwrap();
-            let stored_result = cache_unlocked.get_mut(&cloned_args);
-            if let Some(stored_result) = stored_result {
-                return stored_result.clone();
-            };
+                let is_running = {
+                    let mut running = in_flight.lock().unwrap_or_else(PoisonError::into_inner);
+                    if running.contains_key(&cloned_args) {
+                        true
+                    } else {
+                        running.insert(cloned_args.clone(), Vec::new());
+                        false
+                    }
+                };
+                if !is_running {
+                    break;
+                }
+
+                std::future::poll_fn(|cx| {
+                    match in_flight.lock().unwrap_or_else(PoisonError::into_inner).get_mut(&cloned_args) {
+                        Some(wakers) => {
+                            wakers.push(cx.waker().clone());
+                            Poll::Pending
+                        }
+                        None => Poll::Ready(()),
+                    }
+                }).await;
+            }
+
+            let _guard = InFlightGuard(cloned_args.clone());
+            let ret = #inner_fn_call;
+            if let Ok(mut cache_unlocked) = cache_shard.lock() {
+                cache_unlocked.insert(cloned_args, ret.clone());
+            }
+            ret
+        }
+    }
+}
 
-            // must unlock here to allow potentially recursive call
-            drop(cache_unlocked);
+// Build the body of the caching function which keeps the cache in a field of `self`.
+// The field must be a `RefCell<Cache>`, or a `Mutex<Cache>` when `cache_cfg(thread)` is set.
+fn build_field_cache_body(field: &syn::Ident, cloned_args: &syn::ExprTuple,
+                          inner_fn_call: &syn::Expr, config: &config::Config) -> syn::Block
+{
+    let lock: syn::Expr = if config.use_thread {
+        parse_quote! { self.#field.lock() }
+    } else {
+        parse_quote! { Ok::<_, std::convert::Infallible>(self.#field.borrow_mut()) }
+    };
+    parse_quote! {
+        {
+            let cloned_args = #cloned_args;
+            if let Ok(mut cache_unlocked) = #lock {
+                if let Some(stored_result) = cache_unlocked.get_mut(&cloned_args) {
+                    return stored_result.clone();
+                }
+            }
 
             let ret = #inner_fn_call;
-            let mut cache_unlocked = cache.lock().unwrap();
-            cache_unlocked.insert(cloned_args, ret.clone());
+            if let Ok(mut cache_unlocked) = #lock {
+                cache_unlocked.insert(cloned_args, ret.clone());
+            }
             ret
         }
     }
@@ -172,6 +469,22 @@ fn get_cache_fn_return_type(original_fn: &syn::ItemFn) -> syn::Result<Box<syn::T
     }
 }
 
+// Call the renamed original function, as a method when it takes `self`.
+fn make_inner_fn_call(f: &syn::ItemFn, call_args: Punctuated<syn::Expr, Token![,]>) -> syn::Expr {
+    let ident = &f.sig.ident;
+    if f.sig.receiver().is_some() {
+        parse_quote! { self.#ident(#call_args) }
+    } else {
+        let fn_path = path_from_ident(ident.clone());
+        syn::Expr::Call(syn::ExprCall {
+            attrs: Vec::new(),
+            paren_token: syn::token::Paren::default(),
+            args: call_args,
+            func: Box::new(fn_path)
+        })
+    }
+}
+
 fn path_from_ident(ident: syn::Ident) -> syn::Expr {
     let mut segments: Punctuated<_, Token![::]> = Punctuated::new();
     segments.push(syn::PathSegment { ident: ident, arguments: syn::PathArguments::None });
@@ -199,6 +512,32 @@ fn make_cloned_args_tuple(args: &Punctuated<syn::Expr, Token![,]>) -> syn::ExprT
     }
 }
 
+// The typed arguments which form the cache key, used as the parameters of `<name>_cache_remove`.
+fn get_key_inputs(f: &syn::ItemFn, config: &config::Config) -> Punctuated<syn::FnArg, Token![,]> {
+    let mut inputs = Punctuated::<_, Token![,]>::new();
+    for input in &f.sig.inputs {
+        if let syn::FnArg::Typed(p) = input {
+            if let syn::Pat::Ident(ref pat_ident) = *p.pat {
+                if config.ignore_args.contains(&pat_ident.ident) {
+                    continue;
+                }
+            }
+            if let syn::Type::Reference(type_reference) = &*p.ty {
+                if type_reference.mutability.is_some() {
+                    continue;
+                }
+            }
+            let mut p = p.clone();
+            // `mut x: T` only matters inside the original function
+            if let syn::Pat::Ident(ref mut pat_ident) = *p.pat {
+                pat_ident.mutability = None;
+            }
+            inputs.push(syn::FnArg::Typed(p));
+        }
+    }
+    inputs
+}
+
 fn get_args_and_types(f: &syn::ItemFn, config: &config::Config) ->
         syn::Result<(Punctuated<syn::Expr, Token![,]>, Punctuated<syn::Type, Token![,]>, Punctuated<syn::Expr, Token![,]>)>
 {
@@ -208,9 +547,14 @@ fn get_args_and_types(f: &syn::ItemFn, config: &config::Config) ->
 
     for input in &f.sig.inputs {
         match input {
-            syn::FnArg::Receiver(_) => {
-                return Err(syn::Error::new(input.span(), "`self` arguments are currently unsupported by algorithm_cache"));
-
+            syn::FnArg::Receiver(r) => {
+                // `self` is not part of the key, the cache is stored per instance
+                if config.field.is_none() {
+                    return Err(syn::Error::new(input.span(), "`self` arguments require `cache_cfg(field = ...)` to store the cache"));
+                }
+                if r.reference.is_none() {
+                    return Err(syn::Error::new(input.span(), "`self` by value is unsupported, use `&self` or `&mut self`"));
+                }
             }
             syn::FnArg::Typed(p) => {
                 let mut segments: syn::punctuated::Punctuated<_, Token![::]> = syn::punctuated::Punctuated::new();
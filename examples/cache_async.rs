@@ -0,0 +1,84 @@
+use std::{
+    future::Future,
+    pin::pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+};
+
+use algorithm::LruCache;
+use algorithm_macro::cache;
+
+static CALL_COUNT: AtomicUsize = AtomicUsize::new(0);
+static DEDUP_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on<F: Future>(fut: F) -> F::Output {
+    let mut fut = pin!(fut);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(v) = fut.as_mut().poll(&mut cx) {
+            return v;
+        }
+        thread::park();
+    }
+}
+
+/// 让出一次执行权, 模拟异步io
+async fn yield_now() {
+    let mut yielded = false;
+    std::future::poll_fn(|cx| {
+        if yielded {
+            Poll::Ready(())
+        } else {
+            yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+#[cache(LruCache : LruCache::new(20))]
+async fn slow_async(u: u64) -> u64 {
+    CALL_COUNT.fetch_add(1, Ordering::Relaxed);
+    yield_now().await;
+    u * 10
+}
+
+#[cache(LruCache : LruCache::new(20))]
+#[cache_cfg(dedup)]
+async fn dedup_async(u: u64) -> u64 {
+    DEDUP_COUNT.fetch_add(1, Ordering::Relaxed);
+    yield_now().await;
+    u * 10
+}
+
+fn main() {
+    let ret: u64 = (0..21).map(|v| block_on(slow_async(v % 3))).sum();
+    assert_eq!(ret, 210);
+    assert_eq!(CALL_COUNT.load(Ordering::Relaxed), 3);
+
+    // 两个相同参数的调用同时进行, 只会执行一次
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut first = pin!(dedup_async(5));
+    let mut second = pin!(dedup_async(5));
+    assert!(first.as_mut().poll(&mut cx).is_pending());
+    assert!(second.as_mut().poll(&mut cx).is_pending());
+    assert_eq!(first.as_mut().poll(&mut cx), Poll::Ready(50));
+    assert_eq!(second.as_mut().poll(&mut cx), Poll::Ready(50));
+    assert_eq!(DEDUP_COUNT.load(Ordering::Relaxed), 1);
+    println!("async cache ok");
+}
@@ -1,6 +1,6 @@
-use std::{thread, time::{Duration, Instant}};
+use std::{cell::RefCell, thread, time::{Duration, Instant}};
 
-use algorithm::LruCache;
+use algorithm::{DefaultHasher, LruCache};
 use algorithm_macro::cache;
 
 #[cache(LruCache : LruCache::new(20))]
@@ -21,6 +21,27 @@ fn slow_func(u: u64) -> u64 {
     u * 10
 }
 
+#[cache(LruCache : LruCache::new(20))]
+#[cache_cfg(shard = 8)]
+fn shard_func(u: u64) -> u64 {
+    thread::sleep(Duration::from_millis(100));
+    u * 10
+}
+
+struct Service {
+    base: u64,
+    cache: RefCell<LruCache<(u64,), u64, DefaultHasher>>,
+}
+
+impl Service {
+    #[cache]
+    #[cache_cfg(field = cache)]
+    fn slow_method(&self, u: u64) -> u64 {
+        thread::sleep(Duration::from_millis(100));
+        u * self.base
+    }
+}
+
 fn slow_func_not_cache(u: u64) -> u64 {
     thread::sleep(Duration::from_secs(1));
     u * 10
@@ -35,10 +56,30 @@ fn main() {
     let normal_elapsed = now.elapsed();
 
     assert_eq!(cache_ret, normal_ret);
+    assert_eq!(slow_func_cache_len(), 3);
+    assert_eq!(slow_func_cache_remove(1), Some(10));
+    assert_eq!(slow_func_cache_with(|c| c.get(&(2,)).copied()), Some(20));
+    slow_func_cache_clear();
+    assert_eq!(slow_func_cache_len(), 0);
     assert!(normal_elapsed.as_secs() > cache_elapsed.as_secs() * 6);
 
     println!("cache_elapsed = {}ms", cache_elapsed.as_millis());
     println!("normal_elapsed = {}ms", normal_elapsed.as_millis());
+
+    let service = Service { base: 10, cache: RefCell::new(LruCache::new(20)) };
+    let now = Instant::now();
+    let method_ret: u64 = (0..21).map(|v| service.slow_method(v % 3)).sum();
+    assert_eq!(method_ret, normal_ret);
+    assert!(now.elapsed().as_millis() < 1000);
+    assert_eq!(service.cache.borrow().len(), 3);
+
+    let now = Instant::now();
+    let handles: Vec<_> = (0..4).map(|_| thread::spawn(|| (0..21).map(|v| shard_func(v % 3)).sum::<u64>())).collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), normal_ret);
+    }
+    assert!(now.elapsed().as_millis() < 2000);
+    assert_eq!(shard_func_cache_len(), 3);
     // let mut call_count = 0;
     // assert_eq!(fib(39, &mut call_count), 102_334_155);
     // assert_eq!(call_count, 40);
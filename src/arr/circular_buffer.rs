@@ -146,7 +146,11 @@ impl<T> CircularBuffer<T> {
         if self.is_inited() {
             self.tail = self.add_fix(self.tail);
             self.arr[self.tail] = val;
-            self.head = self.add_fix(self.head);
+            if self.is_full() {
+                self.head = self.add_fix(self.head);
+            } else {
+                self.len += 1;
+            }
         } else {
             if self.tail + 1 < self.arr.len()  {
                 self.tail = self.add_fix(self.tail);
@@ -177,7 +181,11 @@ impl<T> CircularBuffer<T> {
         if self.is_inited() {
             self.head = self.sub_fix(self.head);
             self.arr[self.head] = val;
-            self.tail = self.sub_fix(self.tail);
+            if self.is_full() {
+                self.tail = self.sub_fix(self.tail);
+            } else {
+                self.len += 1;
+            }
         } else {
             if self.head > 0  {
                 self.head = self.sub_fix(self.head);
@@ -450,4 +458,19 @@ mod tests {
         let val: Vec<i32> = circular.iter().rev().map(|s| *s).collect();
         assert_eq!(val, vec![2, 1]);
     }
+
+    #[test]
+    fn test_push_after_pop() {
+        let mut circular = CircularBuffer::new(2);
+        circular.push_back(1);
+        circular.push_back(2);
+        circular.pop_front();
+        circular.push_back(3);
+        assert_eq!(circular.iter().copied().collect::<Vec<_>>(), vec![2, 3]);
+        circular.pop_back();
+        circular.push_front(1);
+        assert_eq!(circular.iter().copied().collect::<Vec<_>>(), vec![1, 2]);
+        circular.push_back(4);
+        assert_eq!(circular.iter().copied().collect::<Vec<_>>(), vec![2, 4]);
+    }
 }
\ No newline at end of file
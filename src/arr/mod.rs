@@ -2,7 +2,9 @@
 mod circular_buffer;
 mod fix_vec;
 mod skip_list;
+mod sliding_window;
 
 pub use circular_buffer::CircularBuffer;
 pub use fix_vec::FixedVec;
-pub use skip_list::{SkipList, SkipNode, SkipIter};
\ No newline at end of file
+pub use skip_list::{SkipList, SkipNode, SkipIter};
+pub use sliding_window::SlidingWindow;
\ No newline at end of file
@@ -0,0 +1,223 @@
+use std::{ops::Add, time::Duration};
+
+use crate::get_milltimestamp;
+
+use super::CircularBuffer;
+
+/// 一个时间桶内的统计
+#[derive(Debug, Clone)]
+struct Bucket<T> {
+    /// 桶的起始时间(毫秒)
+    start: u64,
+    count: u64,
+    sum: T,
+    min: T,
+    max: T,
+    samples: Vec<T>,
+}
+
+/// 按时间分桶的滑动窗口统计, 由`buckets`个时长为`duration`的桶组成,
+/// 桶保存在[`CircularBuffer`]中, 记录及查询时自动淘汰滑出窗口的桶,
+/// 可用于统计QPS及延迟等监控指标
+///
+/// 时间取自[`get_milltimestamp`], 开启`sim` feature后可由`SimClock`控制。
+/// 百分位数需要保存每个样本, 默认不保存, 可由[`SlidingWindow::set_keep_samples`]开启
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "sim")]
+/// use algorithm::{SimClock, SlidingWindow};
+/// use std::time::Duration;
+/// # #[cfg(feature = "sim")]
+/// fn main() {
+///     let mut clock = SimClock::new(0);
+///     // 10个100毫秒的桶, 统计最近1秒
+///     let mut window = SlidingWindow::new(10, Duration::from_millis(100));
+///     window.set_keep_samples(true);
+///     for latency in [5u64, 20, 8, 13] {
+///         window.record(latency);
+///         clock.advance(300);
+///     }
+///     // 最早的样本5已经滑出窗口
+///     assert_eq!(window.count(), 3);
+///     assert_eq!(window.sum(), 41);
+///     assert_eq!(window.min(), Some(8));
+///     assert_eq!(window.max(), Some(20));
+///     assert_eq!(window.percentile(50.0), Some(13));
+///     assert_eq!(window.rate(), 3.0);
+/// }
+/// # #[cfg(not(feature = "sim"))]
+/// # fn main() {}
+/// ```
+pub struct SlidingWindow<T> {
+    buckets: CircularBuffer<Bucket<T>>,
+    /// 桶的数量
+    size: usize,
+    duration: u64,
+    keep_samples: bool,
+}
+
+impl<T> SlidingWindow<T>
+where
+    T: Copy + PartialOrd + Add<Output = T> + Default,
+{
+    /// 创建共`buckets`个桶, 每个桶时长为`duration`的滑动窗口, 时长不足1毫秒的按1毫秒计算
+    pub fn new(buckets: usize, duration: Duration) -> Self {
+        debug_assert!(buckets > 0, "桶的数量必须大于0");
+        Self {
+            buckets: CircularBuffer::new(buckets.max(1)),
+            size: buckets.max(1),
+            duration: (duration.as_millis() as u64).max(1),
+            keep_samples: false,
+        }
+    }
+
+    /// 是否保存每个样本, 开启后才能计算[`SlidingWindow::percentile`]
+    pub fn set_keep_samples(&mut self, keep: bool) {
+        self.keep_samples = keep;
+    }
+
+    /// 整个窗口的时长
+    pub fn window(&self) -> Duration {
+        Duration::from_millis(self.duration * self.size as u64)
+    }
+
+    /// 记录一个样本
+    pub fn record(&mut self, val: T) {
+        self.record_at(get_milltimestamp(), val)
+    }
+
+    fn record_at(&mut self, now: u64, val: T) {
+        self.rotate(now);
+        let start = now - now % self.duration;
+        let keep = self.keep_samples;
+        let add = |bucket: &mut Bucket<T>| {
+            bucket.count += 1;
+            bucket.sum = bucket.sum + val;
+            if val < bucket.min {
+                bucket.min = val;
+            }
+            if val > bucket.max {
+                bucket.max = val;
+            }
+            if keep {
+                bucket.samples.push(val);
+            }
+        };
+        match self.buckets.iter_mut().next_back() {
+            Some(bucket) if bucket.start == start => return add(bucket),
+            // 时间回退时计入仍在窗口内的桶, 对应的桶不存在时丢弃
+            Some(bucket) if bucket.start > start => {
+                if let Some(bucket) = self.buckets.iter_mut().find(|b| b.start == start) {
+                    add(bucket);
+                }
+                return;
+            }
+            _ => {}
+        }
+        self.buckets.push_back(Bucket {
+            start,
+            count: 1,
+            sum: val,
+            min: val,
+            max: val,
+            samples: if keep { vec![val] } else { vec![] },
+        });
+    }
+
+    /// 淘汰滑出窗口的桶
+    fn rotate(&mut self, now: u64) {
+        let span = self.duration * self.size as u64;
+        while let Some(bucket) = self.buckets.iter().next() {
+            if bucket.start + span > now {
+                break;
+            }
+            self.buckets.pop_front();
+        }
+    }
+
+    fn live(&mut self) -> impl Iterator<Item = &Bucket<T>> {
+        self.rotate(get_milltimestamp());
+        self.buckets.iter()
+    }
+
+    /// 窗口内的样本数
+    pub fn count(&mut self) -> u64 {
+        self.live().map(|b| b.count).sum()
+    }
+
+    /// 窗口内样本的总和
+    pub fn sum(&mut self) -> T {
+        self.live().fold(T::default(), |acc, b| acc + b.sum)
+    }
+
+    pub fn min(&mut self) -> Option<T> {
+        self.live()
+            .map(|b| b.min)
+            .reduce(|a, b| if b < a { b } else { a })
+    }
+
+    pub fn max(&mut self) -> Option<T> {
+        self.live()
+            .map(|b| b.max)
+            .reduce(|a, b| if b > a { b } else { a })
+    }
+
+    /// 窗口内平均每秒的样本数
+    pub fn rate(&mut self) -> f64 {
+        let secs = self.window().as_secs_f64();
+        self.count() as f64 / secs
+    }
+
+    /// 窗口内第`p`(0~100)百分位的样本, 未开启保存样本或窗口为空时返回None
+    pub fn percentile(&mut self, p: f64) -> Option<T> {
+        if !self.keep_samples {
+            return None;
+        }
+        let mut samples: Vec<T> = self.live().flat_map(|b| b.samples.iter().copied()).collect();
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let rank = (p.clamp(0.0, 100.0) / 100.0 * samples.len() as f64).ceil() as usize;
+        Some(samples[rank.max(1) - 1])
+    }
+
+    /// 清空所有的桶
+    pub fn clear(&mut self) {
+        while !self.buckets.is_empty() {
+            self.buckets.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::SlidingWindow;
+
+    #[test]
+    fn test_rotate() {
+        let mut window = SlidingWindow::new(4, Duration::from_millis(10));
+        window.set_keep_samples(true);
+        let mut all = vec![];
+        for now in (0..200).step_by(3) {
+            window.record_at(now, now);
+            all.push(now);
+            window.rotate(now);
+            let live: Vec<u64> = all.iter().copied().filter(|v| v / 10 * 10 + 40 > now).collect();
+            let count: u64 = window.buckets.iter().map(|b| b.count).sum();
+            let sum: u64 = window.buckets.iter().map(|b| b.sum).sum();
+            assert_eq!(count, live.len() as u64);
+            assert_eq!(sum, live.iter().sum());
+        }
+        // 时间回退到窗口内的桶仍然计入
+        window.record_at(180, 1000);
+        assert_eq!(window.buckets.iter().map(|b| b.max).max(), Some(1000));
+        // 已滑出窗口的样本被丢弃
+        window.record_at(100, 2000);
+        assert_eq!(window.buckets.iter().map(|b| b.max).max(), Some(1000));
+    }
+}
@@ -0,0 +1,560 @@
+// Copyright 2022 - 2024 Wenmeng See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// Author: tickbh
+// -----
+// Created Date: 2026/10/18 10:12:30
+
+use std::{
+    iter, mem,
+    ops::{Index, IndexMut},
+    slice,
+};
+
+use super::Reinit;
+
+/// 带代数的键值, 槽位每次被删除后代数加1, 旧的键值将无法再访问到新的对象
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GenKey {
+    index: usize,
+    generation: u32,
+}
+
+impl GenKey {
+    /// 所在的槽位
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// 生成该键值时槽位的代数
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Entry<T: Default> {
+    t: T,
+    next: usize,
+    generation: u32,
+}
+
+impl<T: Default> Entry<T> {
+    pub fn new() -> Self {
+        Self {
+            t: T::default(),
+            next: usize::MAX,
+            generation: 0,
+        }
+    }
+
+    pub fn is_occupied(&self) -> bool {
+        self.next == usize::MAX
+    }
+
+    pub fn is_match(&self, key: &GenKey) -> bool {
+        self.is_occupied() && self.generation == key.generation
+    }
+}
+
+/// 带代数检查的Slab, 与[`crate::Slab`]一样会将对象重复循环使用
+/// 键值中带有槽位的代数, 删除后仍持有的旧键值将获取不到复用后的对象
+///
+/// # Examples
+///
+/// ```
+/// use algorithm::GenSlab;
+/// fn main() {
+///     let mut slab = GenSlab::new();
+///     let k = slab.insert("hello");
+///     slab.remove(k);
+///     let k1 = slab.insert("slab");
+///     assert!(k.index() == k1.index());
+///     assert!(slab.try_get(k) == None);
+///     assert!(slab.try_get(k1) == Some(&"slab"));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct GenSlab<T: Default> {
+    entries: Vec<Entry<T>>,
+    len: usize,
+    next: usize,
+}
+
+impl<T: Default> GenSlab<T> {
+    pub fn new() -> Self {
+        GenSlab {
+            entries: vec![],
+            len: 0,
+            next: 0,
+        }
+    }
+
+    /// 获取当前长度
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 是否为空表
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 清除数据, 对象保留以供复用, 之前的键值将全部失效
+    ///
+    /// ```
+    /// use algorithm::GenSlab;
+    /// fn main() {
+    ///     let mut slab = GenSlab::new();
+    ///     let k = slab.insert("slab");
+    ///     slab.clear();
+    ///     assert!(slab.len() == 0);
+    ///     assert!(slab.contains_key(k) == false);
+    /// }
+    /// ```
+    pub fn clear(&mut self) {
+        self.next = self.entries.len();
+        for (i, entry) in self.entries.iter_mut().enumerate().rev() {
+            if entry.is_occupied() {
+                entry.generation = entry.generation.wrapping_add(1);
+            }
+            entry.next = self.next;
+            self.next = i;
+        }
+        self.len = 0;
+    }
+
+    /// 获取key相对应的value值, 键值失效时将panic
+    ///
+    /// ```
+    /// use algorithm::GenSlab;
+    /// fn main() {
+    ///     let mut slab = GenSlab::new();
+    ///     let k = slab.insert("slab");
+    ///     assert!(slab.get(k) == &"slab");
+    /// }
+    /// ```
+    pub fn get(&self, key: GenKey) -> &T {
+        match self.try_get(key) {
+            Some(t) => t,
+            None => panic!("stale or invalid key"),
+        }
+    }
+
+    /// 尝试获取key下的值, 键值已被删除或槽位已被复用时返回None
+    pub fn try_get(&self, key: GenKey) -> Option<&T> {
+        match self.entries.get(key.index) {
+            Some(entry) if entry.is_match(&key) => Some(&entry.t),
+            _ => None,
+        }
+    }
+
+    /// 获取key相对应的value可变值, 键值失效时将panic
+    ///
+    /// ```
+    /// use algorithm::GenSlab;
+    /// fn main() {
+    ///     let mut slab = GenSlab::new();
+    ///     let k = slab.insert("slab");
+    ///     assert!(slab.get_mut(k) == &mut "slab");
+    /// }
+    /// ```
+    pub fn get_mut(&mut self, key: GenKey) -> &mut T {
+        match self.try_get_mut(key) {
+            Some(t) => t,
+            None => panic!("stale or invalid key"),
+        }
+    }
+
+    /// 尝试获取key下的可变值, 键值已被删除或槽位已被复用时返回None
+    pub fn try_get_mut(&mut self, key: GenKey) -> Option<&mut T> {
+        match self.entries.get_mut(key.index) {
+            Some(entry) if entry.is_match(&key) => Some(&mut entry.t),
+            _ => None,
+        }
+    }
+
+    /// 获取下一个的key值和val值, val可能为上一次使用后的值
+    ///
+    /// ```
+    /// use algorithm::GenSlab;
+    /// fn main() {
+    ///     let mut slab = GenSlab::new();
+    ///     let k = slab.insert("slab");
+    ///     let (k1, v) = slab.get_next_val();
+    ///     assert!(k1.index() == 1 && v == &mut "");
+    /// }
+    /// ```
+    pub fn get_next_val(&mut self) -> (GenKey, &mut T) {
+        let key = self.get_next();
+        (key, &mut self.entries[key.index].t)
+    }
+
+    /// 获取下一个的key值
+    ///
+    /// ```
+    /// use algorithm::GenSlab;
+    /// fn main() {
+    ///     let mut slab = GenSlab::new();
+    ///     let k = slab.insert("slab");
+    ///     assert!(slab.get_next().index() == 1);
+    /// }
+    /// ```
+    pub fn get_next(&mut self) -> GenKey {
+        if self.entries.len() == self.len {
+            self.entries.push(Entry::new());
+            self.len += 1;
+            GenKey {
+                index: self.len - 1,
+                generation: 0,
+            }
+        } else {
+            let index = self.next;
+            let entry = &mut self.entries[index];
+            if entry.is_occupied() {
+                unreachable!()
+            }
+            self.len += 1;
+            self.next = entry.next;
+            entry.next = usize::MAX;
+            GenKey {
+                index,
+                generation: entry.generation,
+            }
+        }
+    }
+
+    /// 插入一条数据进入slab缓存起来
+    ///
+    /// ```
+    /// use algorithm::GenSlab;
+    /// fn main() {
+    ///     let mut slab = GenSlab::new();
+    ///     let k = slab.insert("slab");
+    ///     assert!(slab[k] == "slab");
+    /// }
+    /// ```
+    pub fn insert(&mut self, mut val: T) -> GenKey {
+        let (key, value) = self.get_next_val();
+        mem::swap(value, &mut val);
+        key
+    }
+
+    /// 删除某个键值数据, 键值失效时将panic
+    #[inline(always)]
+    pub fn remove(&mut self, key: GenKey) {
+        if !self.try_remove(key) {
+            panic!("stale or invalid key")
+        }
+    }
+
+    /// 试图删除某个键值数据, 内容保留以供下次复用, 同时槽位的代数加1
+    ///
+    /// ```
+    /// use algorithm::GenSlab;
+    /// fn main() {
+    ///     let mut slab = GenSlab::new();
+    ///     let k = slab.insert("slab");
+    ///     assert!(slab.try_remove(k) == true);
+    ///     assert!(slab.try_remove(k) == false);
+    ///     let k1 = slab.insert("slab");
+    ///     assert!(slab.try_remove(k) == false);
+    ///     assert!(slab.len() == 1);
+    /// }
+    /// ```
+    pub fn try_remove(&mut self, key: GenKey) -> bool {
+        let entry = match self.entries.get_mut(key.index) {
+            Some(entry) if entry.is_match(&key) => entry,
+            _ => return false,
+        };
+        entry.generation = entry.generation.wrapping_add(1);
+        entry.next = self.next;
+        self.next = key.index;
+        self.len -= 1;
+        true
+    }
+
+    /// 是否包含某个键值, 旧代数的键值返回false
+    pub fn contains_key(&self, key: GenKey) -> bool {
+        self.try_get(key).is_some()
+    }
+
+    /// 遍历当前的所有值
+    ///
+    /// ```
+    /// use algorithm::GenSlab;
+    /// fn main() {
+    ///     let mut slab = GenSlab::new();
+    ///     slab.insert("hello");
+    ///     slab.insert("this");
+    ///     let mut iter = slab.iter();
+    ///     assert!(iter.next().map(|(_, v)| v) == Some(&"hello"));
+    ///     assert!(iter.next().map(|(_, v)| v) == Some(&"this"));
+    ///     assert!(iter.next() == None);
+    /// }
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            entries: self.entries.iter().enumerate(),
+            len: self.len,
+        }
+    }
+
+    /// 遍历当前的所有值, 可同时修改值
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            entries: self.entries.iter_mut().enumerate(),
+            len: self.len,
+        }
+    }
+
+    /// 根据保留当前的元素, 返回false则表示抛弃元素
+    ///
+    /// ```
+    /// use algorithm::GenSlab;
+    /// fn main() {
+    ///     let mut slab = GenSlab::new();
+    ///     slab.insert("hello");
+    ///     let k = slab.insert("this");
+    ///     slab.insert("year");
+    ///     slab.retain(|_, v| *v == "hello" || *v == "this");
+    ///     assert!(slab.len() == 2);
+    ///     assert!(slab.get(k) == &"this");
+    /// }
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(GenKey, &mut T) -> bool,
+    {
+        for index in 0..self.entries.len() {
+            let entry = &mut self.entries[index];
+            if !entry.is_occupied() {
+                continue;
+            }
+            let key = GenKey {
+                index,
+                generation: entry.generation,
+            };
+            if !f(key, &mut entry.t) {
+                self.remove(key);
+            }
+        }
+    }
+}
+
+impl<T: Default + Reinit> GenSlab<T> {
+    /// 获取下一个key并重新初始化
+    ///
+    /// ```
+    /// use algorithm::GenSlab;
+    /// fn main() {
+    ///     let mut slab = GenSlab::new();
+    ///     let k = slab.insert("slab");
+    ///     slab.remove(k);
+    ///     let k1 = slab.get_reinit_next();
+    ///     assert!(slab.get(k1) == &"");
+    /// }
+    /// ```
+    pub fn get_reinit_next(&mut self) -> GenKey {
+        let key = self.get_next();
+        self.entries[key.index].t.reinit();
+        key
+    }
+
+    /// 获取下一个key和val并重新初始化
+    #[inline(always)]
+    pub fn get_reinit_next_val(&mut self) -> (GenKey, &mut T) {
+        let key = self.get_next();
+        let t = &mut self.entries[key.index].t;
+        t.reinit();
+        (key, t)
+    }
+}
+
+impl<T: Default> Default for GenSlab<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Default> Index<GenKey> for GenSlab<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, key: GenKey) -> &T {
+        self.get(key)
+    }
+}
+
+impl<T: Default> IndexMut<GenKey> for GenSlab<T> {
+    #[inline]
+    fn index_mut(&mut self, key: GenKey) -> &mut T {
+        self.get_mut(key)
+    }
+}
+
+pub struct Iter<'a, T: Default> {
+    entries: iter::Enumerate<slice::Iter<'a, Entry<T>>>,
+    len: usize,
+}
+
+pub struct IterMut<'a, T: Default> {
+    entries: iter::Enumerate<slice::IterMut<'a, Entry<T>>>,
+    len: usize,
+}
+
+impl<'a, T: Default> Iterator for Iter<'a, T> {
+    type Item = (GenKey, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, entry) in &mut self.entries {
+            if entry.is_occupied() {
+                self.len -= 1;
+                let key = GenKey {
+                    index,
+                    generation: entry.generation,
+                };
+                return Some((key, &entry.t));
+            }
+        }
+
+        debug_assert_eq!(self.len, 0);
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T: Default> Iterator for IterMut<'a, T> {
+    type Item = (GenKey, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, entry) in &mut self.entries {
+            if entry.is_occupied() {
+                self.len -= 1;
+                let key = GenKey {
+                    index,
+                    generation: entry.generation,
+                };
+                return Some((key, &mut entry.t));
+            }
+        }
+
+        debug_assert_eq!(self.len, 0);
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T: Default> FromIterator<T> for GenSlab<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> GenSlab<T> {
+        let mut slab = GenSlab::new();
+        slab.extend(iter);
+        slab
+    }
+}
+
+impl<T: Default> Extend<T> for GenSlab<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for v in iter {
+            self.insert(v);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GenSlab;
+
+    #[test]
+    fn test_insert() {
+        let mut m = GenSlab::new();
+        assert_eq!(m.len(), 0);
+        let k = m.insert(1);
+        assert_eq!(m.len(), 1);
+        m.insert(2);
+        assert_eq!(m.len(), 2);
+        assert_eq!(m.get(k), &1);
+    }
+
+    #[test]
+    fn test_stale_key() {
+        let mut m = GenSlab::new();
+        let k = m.insert(1);
+        m.remove(k);
+        assert_eq!(m.try_get(k), None);
+        let k1 = m.insert(2);
+        assert_eq!(k.index(), k1.index());
+        assert_ne!(k.generation(), k1.generation());
+        assert_eq!(m.try_get(k), None);
+        assert_eq!(m.try_get_mut(k), None);
+        assert!(!m.try_remove(k));
+        assert_eq!(m.try_get(k1), Some(&2));
+    }
+
+    #[test]
+    fn test_reinit_reuse() {
+        let mut m = GenSlab::new();
+        let k = m.insert("slab".to_string());
+        m.remove(k);
+        let (k1, v) = m.get_next_val();
+        assert_eq!(v, "slab");
+        assert_eq!(k.index(), k1.index());
+        m.remove(k1);
+        let (k2, v) = m.get_reinit_next_val();
+        assert_eq!(v, "");
+        assert!(!m.contains_key(k1));
+        assert!(m.contains_key(k2));
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut m = GenSlab::new();
+        let keys: Vec<_> = (0..10).map(|i| m.insert(i)).collect();
+        m.clear();
+        assert!(m.is_empty());
+        let new_keys: Vec<_> = (0..10).map(|i| m.insert(i)).collect();
+        for (k, nk) in keys.iter().zip(new_keys.iter()) {
+            assert_eq!(k.index(), nk.index());
+            assert!(!m.contains_key(*k));
+            assert!(m.contains_key(*nk));
+        }
+        assert_eq!(m.insert(10).index(), 10);
+    }
+
+    #[test]
+    fn test_iterate() {
+        let mut m = GenSlab::new();
+        for i in 0..32 {
+            m.insert(i * 2);
+        }
+        m.retain(|k, _| k.index() % 2 == 0);
+        assert_eq!(m.len(), 16);
+        for (k, v) in m.iter_mut() {
+            assert_eq!(*v, k.index() * 2);
+            *v += 1;
+        }
+        let sum: usize = m.iter().map(|(_, v)| *v).sum();
+        assert_eq!(sum, (0..32).filter(|i| i % 2 == 0).map(|i| i * 2 + 1).sum());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_index_stale() {
+        let mut m = GenSlab::new();
+        let k = m.insert(1);
+        m.remove(k);
+        m.insert(2);
+        let _ = m[k];
+    }
+}
@@ -5,9 +5,15 @@ mod lru;
 mod lruk;
 mod arc;
 mod slab;
+mod gen_slab;
+mod slab_pool;
+mod page_slab;
 
 pub use lru::LruCache;
 pub use lruk::LruKCache;
 pub use lfu::LfuCache;
 pub use arc::ArcCache;
 pub use slab::{Slab, Reinit};
+pub use gen_slab::{GenSlab, GenKey};
+pub use slab_pool::{SlabPool, PoolGuard};
+pub use page_slab::PageSlab;
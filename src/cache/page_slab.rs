@@ -0,0 +1,493 @@
+// Copyright 2022 - 2024 Wenmeng See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// Author: tickbh
+// -----
+// Created Date: 2026/10/18 11:40:12
+
+use std::{
+    iter, mem,
+    ops::{Index, IndexMut},
+    ptr::NonNull,
+    slice,
+};
+
+use super::{slab::Entry, Reinit};
+
+const DEFAULT_PAGE_SIZE: usize = 32;
+
+/// 分页存储的Slab, 与[`crate::Slab`]一样会将对象重复循环使用
+/// 对象按页分配, 每页的容量固定, 扩容时只分配新的页, 已分配的对象永远不会移动
+/// 因此扩容时不会拷贝大对象, 且可通过`get_ptr`获取稳定的指针
+///
+/// # Examples
+///
+/// ```
+/// use algorithm::PageSlab;
+/// fn main() {
+///     let mut slab = PageSlab::with_page_size(4);
+///     let k = slab.insert("hello".to_string());
+///     let ptr = slab.get_ptr(k).unwrap();
+///     for i in 0..100 {
+///         slab.insert(format!("{}", i));
+///     }
+///     assert!(slab.page_count() == 26);
+///     assert!(unsafe { ptr.as_ref() } == "hello");
+/// }
+/// ```
+#[derive(Debug)]
+pub struct PageSlab<T: Default> {
+    pages: Vec<Vec<Entry<T>>>,
+    page_bits: u32,
+    /// 已分配的对象数量
+    allocated: usize,
+    len: usize,
+    next: usize,
+}
+
+impl<T: Default> PageSlab<T> {
+    pub fn new() -> Self {
+        Self::with_page_size(DEFAULT_PAGE_SIZE)
+    }
+
+    /// 指定每页的对象数量, 将向上取整为2的幂
+    pub fn with_page_size(page_size: usize) -> Self {
+        PageSlab {
+            pages: vec![],
+            page_bits: page_size.max(1).next_power_of_two().trailing_zeros(),
+            allocated: 0,
+            len: 0,
+            next: 0,
+        }
+    }
+
+    /// 每页的对象数量
+    pub fn page_size(&self) -> usize {
+        1 << self.page_bits
+    }
+
+    /// 已分配的页数
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// 获取当前长度
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 是否为空表
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 清除数据, 已分配的页保留以供复用
+    ///
+    /// ```
+    /// use algorithm::PageSlab;
+    /// fn main() {
+    ///     let mut slab = PageSlab::new();
+    ///     slab.insert("slab");
+    ///     slab.clear();
+    ///     assert!(slab.len() == 0);
+    ///     assert!(slab.get_next_val() == (0, &mut "slab"));
+    /// }
+    /// ```
+    pub fn clear(&mut self) {
+        self.next = self.allocated;
+        for i in (0..self.allocated).rev() {
+            self.entry_mut(i).next = self.next;
+            self.next = i;
+        }
+        self.len = 0;
+    }
+
+    #[inline(always)]
+    fn locate(&self, key: usize) -> (usize, usize) {
+        (key >> self.page_bits, key & ((1 << self.page_bits) - 1))
+    }
+
+    #[inline(always)]
+    fn entry(&self, key: usize) -> &Entry<T> {
+        let (page, offset) = self.locate(key);
+        &self.pages[page][offset]
+    }
+
+    #[inline(always)]
+    fn entry_mut(&mut self, key: usize) -> &mut Entry<T> {
+        let (page, offset) = self.locate(key);
+        &mut self.pages[page][offset]
+    }
+
+    fn try_entry(&self, key: usize) -> Option<&Entry<T>> {
+        if key >= self.allocated {
+            return None;
+        }
+        Some(self.entry(key)).filter(|e| e.is_occupied())
+    }
+
+    fn try_entry_mut(&mut self, key: usize) -> Option<&mut Entry<T>> {
+        if key >= self.allocated {
+            return None;
+        }
+        Some(self.entry_mut(key)).filter(|e| e.is_occupied())
+    }
+
+    /// 获取index值相对应的value值
+    ///
+    /// ```
+    /// use algorithm::PageSlab;
+    /// fn main() {
+    ///     let mut slab = PageSlab::new();
+    ///     let k = slab.insert("slab");
+    ///     assert!(slab.get(k) == &"slab");
+    /// }
+    /// ```
+    pub fn get(&self, key: usize) -> &T {
+        let entry = self.entry(key);
+        debug_assert!(entry.is_occupied());
+        &entry.t
+    }
+
+    /// 尝试获取key下的值
+    pub fn try_get(&self, key: usize) -> Option<&T> {
+        self.try_entry(key).map(|e| &e.t)
+    }
+
+    /// 获取index值相对应的value可变值
+    pub fn get_mut(&mut self, key: usize) -> &mut T {
+        let entry = self.entry_mut(key);
+        debug_assert!(entry.is_occupied());
+        &mut entry.t
+    }
+
+    /// 尝试获取key下的可变值
+    pub fn try_get_mut(&mut self, key: usize) -> Option<&mut T> {
+        self.try_entry_mut(key).map(|e| &mut e.t)
+    }
+
+    /// 获取key下对象的指针, 该对象在slab释放前地址均不会改变
+    /// 删除后该地址上的对象可能被复用, 使用时需自行保证有效
+    pub fn get_ptr(&mut self, key: usize) -> Option<NonNull<T>> {
+        self.try_entry_mut(key).map(|e| NonNull::from(&mut e.t))
+    }
+
+    /// 获取下一个的key值和val值
+    ///
+    /// ```
+    /// use algorithm::PageSlab;
+    /// fn main() {
+    ///     let mut slab = PageSlab::new();
+    ///     let k = slab.insert("slab");
+    ///     assert!(slab.get_next_val() == (1, &mut ""));
+    /// }
+    /// ```
+    pub fn get_next_val(&mut self) -> (usize, &mut T) {
+        let key = self.get_next();
+        (key, &mut self.entry_mut(key).t)
+    }
+
+    /// 获取下一个的key值
+    pub fn get_next(&mut self) -> usize {
+        if self.allocated == self.len {
+            let page_size = self.page_size();
+            match self.pages.last_mut() {
+                Some(page) if page.len() < page_size => page.push(Entry::new()),
+                _ => {
+                    // 预先分配整页的容量, 之后的push不会重新分配内存
+                    let mut page = Vec::with_capacity(page_size);
+                    page.push(Entry::new());
+                    self.pages.push(page);
+                }
+            }
+            self.allocated += 1;
+            self.len += 1;
+            self.len - 1
+        } else {
+            let key = self.next;
+            let entry = self.entry_mut(key);
+            if entry.is_occupied() {
+                unreachable!()
+            }
+            let next = entry.next;
+            entry.next = usize::MAX;
+            self.next = next;
+            self.len += 1;
+            key
+        }
+    }
+
+    /// 插入一条数据进入slab缓存起来
+    ///
+    /// ```
+    /// use algorithm::PageSlab;
+    /// fn main() {
+    ///     let mut slab = PageSlab::new();
+    ///     let k = slab.insert("slab");
+    ///     assert!(slab[&k] == "slab");
+    /// }
+    /// ```
+    pub fn insert(&mut self, mut val: T) -> usize {
+        let (key, value) = self.get_next_val();
+        mem::swap(value, &mut val);
+        key
+    }
+
+    /// 删除某个键值数据, 不会返回内容, 因为该内容会提供给下次复用
+    #[inline(always)]
+    pub fn remove(&mut self, key: usize) {
+        if !self.try_remove(key) {
+            panic!("index error")
+        }
+    }
+
+    /// 试图删除某个键值数据, 不会返回内容, 因为该内容会提供给下次复用
+    ///
+    /// ```
+    /// use algorithm::PageSlab;
+    /// fn main() {
+    ///     let mut slab = PageSlab::new();
+    ///     let k = slab.insert("slab");
+    ///     assert!(slab.try_remove(k) == true);
+    ///     assert!(slab.try_remove(k) == false);
+    ///     assert!(slab.len() == 0);
+    /// }
+    /// ```
+    pub fn try_remove(&mut self, key: usize) -> bool {
+        let next = self.next;
+        match self.try_entry_mut(key) {
+            Some(entry) => entry.next = next,
+            None => return false,
+        }
+        self.next = key;
+        self.len -= 1;
+        true
+    }
+
+    /// 是否包含某个键值
+    pub fn contains_key(&self, key: usize) -> bool {
+        self.try_entry(key).is_some()
+    }
+
+    /// 遍历当前的所有值
+    ///
+    /// ```
+    /// use algorithm::PageSlab;
+    /// fn main() {
+    ///     let mut slab = PageSlab::with_page_size(1);
+    ///     slab.insert("hello");
+    ///     slab.insert("this");
+    ///     let mut iter = slab.iter();
+    ///     assert!(iter.next() == Some((0, &"hello")));
+    ///     assert!(iter.next() == Some((1, &"this")));
+    ///     assert!(iter.next() == None);
+    /// }
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            entries: self.pages.iter().flatten().enumerate(),
+            len: self.len,
+        }
+    }
+
+    /// 遍历当前的所有值, 可同时修改值
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            entries: self.pages.iter_mut().flatten().enumerate(),
+            len: self.len,
+        }
+    }
+
+    /// 根据保留当前的元素, 返回false则表示抛弃元素
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(usize, &mut T) -> bool,
+    {
+        for i in 0..self.allocated {
+            let entry = self.entry_mut(i);
+            if entry.is_occupied() && !f(i, &mut entry.t) {
+                self.remove(i);
+            }
+        }
+    }
+}
+
+impl<T: Default + Reinit> PageSlab<T> {
+    /// 获取下一个key并重新初始化
+    pub fn get_reinit_next(&mut self) -> usize {
+        let key = self.get_next();
+        self.entry_mut(key).t.reinit();
+        key
+    }
+
+    /// 获取下一个key和val并重新初始化
+    ///
+    /// ```
+    /// use algorithm::PageSlab;
+    /// fn main() {
+    ///     let mut slab = PageSlab::new();
+    ///     let k = slab.insert("hello");
+    ///     slab.remove(k);
+    ///     assert!(slab.get_reinit_next_val() == (k, &mut ""));
+    /// }
+    /// ```
+    #[inline(always)]
+    pub fn get_reinit_next_val(&mut self) -> (usize, &mut T) {
+        let key = self.get_next();
+        let t = &mut self.entry_mut(key).t;
+        t.reinit();
+        (key, t)
+    }
+}
+
+impl<T: Default> Default for PageSlab<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Default> Index<&usize> for PageSlab<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: &usize) -> &T {
+        &self.entry(*index).t
+    }
+}
+
+impl<T: Default> IndexMut<&usize> for PageSlab<T> {
+    #[inline]
+    fn index_mut(&mut self, index: &usize) -> &mut T {
+        &mut self.entry_mut(*index).t
+    }
+}
+
+pub struct Iter<'a, T: Default> {
+    entries: iter::Enumerate<iter::Flatten<slice::Iter<'a, Vec<Entry<T>>>>>,
+    len: usize,
+}
+
+pub struct IterMut<'a, T: Default> {
+    entries: iter::Enumerate<iter::Flatten<slice::IterMut<'a, Vec<Entry<T>>>>>,
+    len: usize,
+}
+
+impl<'a, T: Default> Iterator for Iter<'a, T> {
+    type Item = (usize, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (key, entry) in &mut self.entries {
+            if entry.is_occupied() {
+                self.len -= 1;
+                return Some((key, &entry.t));
+            }
+        }
+
+        debug_assert_eq!(self.len, 0);
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T: Default> Iterator for IterMut<'a, T> {
+    type Item = (usize, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (key, entry) in &mut self.entries {
+            if entry.is_occupied() {
+                self.len -= 1;
+                return Some((key, &mut entry.t));
+            }
+        }
+
+        debug_assert_eq!(self.len, 0);
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T: Default> FromIterator<T> for PageSlab<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> PageSlab<T> {
+        let mut slab = PageSlab::new();
+        slab.extend(iter);
+        slab
+    }
+}
+
+impl<T: Default> Extend<T> for PageSlab<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for v in iter {
+            self.insert(v);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PageSlab;
+
+    #[test]
+    fn test_insert_remove() {
+        let mut m = PageSlab::with_page_size(3);
+        assert_eq!(m.page_size(), 4);
+        for i in 0..10 {
+            assert_eq!(m.insert(i), i);
+        }
+        assert_eq!(m.page_count(), 3);
+        assert_eq!(m.get(9), &9);
+        m.remove(5);
+        m.remove(2);
+        assert_eq!(m.len(), 8);
+        assert_eq!(m.try_get(5), None);
+        assert_eq!(m.try_get(100), None);
+        assert_eq!(m.get_next(), 2);
+        assert_eq!(m.get_next(), 5);
+        assert_eq!(m.get_next(), 10);
+        assert_eq!(m.page_count(), 3);
+    }
+
+    #[test]
+    fn test_stable_address() {
+        let mut m = PageSlab::with_page_size(2);
+        let first = m.insert([7usize; 32]);
+        let ptr = m.get_ptr(first).unwrap();
+        for _ in 0..1000 {
+            m.insert([0usize; 32]);
+        }
+        assert_eq!(m.get_ptr(first), Some(ptr));
+        assert_eq!(unsafe { ptr.as_ref() }[31], 7);
+        let addrs: Vec<_> = (0..m.len()).map(|k| m.get_ptr(k).unwrap()).collect();
+        m.clear();
+        for k in 0..addrs.len() {
+            let (nk, _) = m.get_next_val();
+            assert_eq!(m.get_ptr(nk), Some(addrs[k]));
+        }
+    }
+
+    #[test]
+    fn test_iterate() {
+        let mut m = PageSlab::with_page_size(4);
+        for i in 0..32 {
+            m.insert(i * 2);
+        }
+        m.retain(|k, _| k % 2 == 0);
+        assert_eq!(m.len(), 16);
+        for (k, v) in m.iter_mut() {
+            assert_eq!(*v, k * 2);
+            *v += 1;
+        }
+        assert_eq!(m.iter().count(), 16);
+        assert_eq!(m[&4], 9);
+    }
+}
@@ -70,9 +70,9 @@ impl<T> Reinit for Vec<T> {
 }
 
 #[derive(Debug)]
-struct Entry<T: Default> {
-    t: T,
-    next: usize,
+pub(crate) struct Entry<T: Default> {
+    pub(crate) t: T,
+    pub(crate) next: usize,
 }
 
 impl<T: Default + Clone> Clone for Entry<T> {
@@ -128,6 +128,7 @@ pub struct Slab<T: Default> {
     entries: Vec<Entry<T>>,
     len: usize,
     next: usize,
+    max_free: usize,
 }
 
 impl<T: Default> Slab<T> {
@@ -136,9 +137,40 @@ impl<T: Default> Slab<T> {
             entries: vec![],
             len: 0,
             next: 0,
+            max_free: usize::MAX,
         }
     }
 
+    /// 空闲待复用的对象数量
+    pub fn free_len(&self) -> usize {
+        self.entries.len() - self.len
+    }
+
+    /// 设置最多保留的空闲对象数量, 默认不限制
+    /// 超出后删除的对象将被替换为Default值以释放其持有的资源, 若处于末尾则直接释放
+    ///
+    /// ```
+    /// use algorithm::Slab;
+    /// fn main() {
+    ///     let mut slab = Slab::new();
+    ///     slab.set_max_free(1);
+    ///     let k = slab.insert("hello".to_string());
+    ///     let k1 = slab.insert("slab".to_string());
+    ///     slab.remove(k);
+    ///     slab.remove(k1);
+    ///     assert!(slab.free_len() == 1);
+    ///     assert!(slab.get_next_val() == (k, &mut "hello".to_string()));
+    /// }
+    /// ```
+    pub fn set_max_free(&mut self, max_free: usize) {
+        self.max_free = max_free;
+    }
+
+    /// 获取最多保留的空闲对象数量
+    pub fn get_max_free(&self) -> usize {
+        self.max_free
+    }
+
     /// 获取当前长度
     pub fn len(&self) -> usize {
         self.len
@@ -318,16 +350,125 @@ impl<T: Default> Slab<T> {
         if key >= self.entries.len() {
             return false;
         }
-        let entry = &mut self.entries[key];
-        if !entry.is_occupied() {
+        if !self.entries[key].is_occupied() {
             return false;
         }
         self.len -= 1;
+        let over_free = self.entries.len() - self.len > self.max_free;
+        if over_free && key == self.entries.len() - 1 {
+            self.entries.pop();
+            return true;
+        }
+        let entry = &mut self.entries[key];
+        if over_free {
+            entry.t = T::default();
+        }
         entry.next = self.next;
         self.next = key;
         true
     }
 
+    /// 释放空闲对象, 最多保留`max_free`个空闲对象
+    /// 末尾的空闲对象将直接释放, 处于中间的空闲对象将替换为Default值
+    /// 若需释放全部内存可先调用`compact`
+    ///
+    /// ```
+    /// use algorithm::Slab;
+    /// fn main() {
+    ///     let mut slab = Slab::new();
+    ///     for i in 0..10 {
+    ///         slab.insert(i);
+    ///     }
+    ///     for i in 2..10 {
+    ///         slab.remove(i);
+    ///     }
+    ///     slab.shrink_to(3);
+    ///     assert!(slab.len() == 2);
+    ///     assert!(slab.free_len() == 3);
+    ///     slab.shrink_to(0);
+    ///     assert!(slab.free_len() == 0);
+    ///     assert!(slab.get_next() == 2);
+    /// }
+    /// ```
+    pub fn shrink_to(&mut self, max_free: usize) {
+        while self.free_len() > max_free {
+            match self.entries.last() {
+                Some(entry) if !entry.is_occupied() => {
+                    self.entries.pop();
+                }
+                _ => break,
+            }
+        }
+        let mut over = self.free_len().saturating_sub(max_free);
+        for entry in self.entries.iter_mut().rev() {
+            if over == 0 {
+                break;
+            }
+            if !entry.is_occupied() {
+                entry.t = T::default();
+                over -= 1;
+            }
+        }
+        self.entries.shrink_to_fit();
+        self.rebuild_free_list();
+    }
+
+    /// 将使用中的对象移动到前部, 并按`max_free`的配置释放空闲对象
+    /// 返回所有被移动的对象的旧键值与新键值, 之前持有的键值需按此重新映射
+    ///
+    /// ```
+    /// use algorithm::Slab;
+    /// fn main() {
+    ///     let mut slab = Slab::new();
+    ///     for i in 0..5 {
+    ///         slab.insert(i);
+    ///     }
+    ///     slab.remove(0);
+    ///     slab.remove(2);
+    ///     let remap = slab.compact();
+    ///     assert!(remap == vec![(4, 0), (3, 2)]);
+    ///     assert!(slab[&0] == 4 && slab[&1] == 1 && slab[&2] == 3);
+    ///     slab.set_max_free(0);
+    ///     slab.compact();
+    ///     assert!(slab.free_len() == 0);
+    /// }
+    /// ```
+    pub fn compact(&mut self) -> Vec<(usize, usize)> {
+        let mut remap = vec![];
+        let mut front = 0;
+        let mut back = self.entries.len();
+        loop {
+            while front < back && self.entries[front].is_occupied() {
+                front += 1;
+            }
+            while back > front && !self.entries[back - 1].is_occupied() {
+                back -= 1;
+            }
+            if back <= front + 1 {
+                break;
+            }
+            back -= 1;
+            self.entries.swap(front, back);
+            remap.push((back, front));
+        }
+        let keep = self.len + self.free_len().min(self.max_free);
+        self.entries.truncate(keep);
+        self.entries.shrink_to_fit();
+        self.rebuild_free_list();
+        remap
+    }
+
+    /// 重新按顺序串联所有空闲的位置, 优先复用靠前的位置
+    fn rebuild_free_list(&mut self) {
+        self.next = self.entries.len();
+        for i in (0..self.entries.len()).rev() {
+            if !self.entries[i].is_occupied() {
+                self.entries[i].next = self.next;
+                self.next = i;
+            }
+        }
+    }
+
     /// 是否包含某个键值
     ///
     /// ```
@@ -487,7 +628,7 @@ impl<T: Default + Reinit> Slab<T> {
 
 impl<T: Default+Clone> Clone for Slab<T> {
     fn clone(&self) -> Self {
-        Self { entries: self.entries.clone(), len: self.len.clone(), next: self.next.clone() }
+        Self { entries: self.entries.clone(), len: self.len, next: self.next, max_free: self.max_free }
     }
 }
 
@@ -844,6 +985,54 @@ mod tests {
         assert_eq!(a[&2], "three");
     }
 
+    #[test]
+    fn test_max_free() {
+        let mut m = Slab::new();
+        m.set_max_free(2);
+        for i in 0..10 {
+            m.insert(vec![i; 100]);
+        }
+        for i in 0..9 {
+            m.remove(i);
+        }
+        assert_eq!(m.len(), 1);
+        assert_eq!(m.free_len(), 9);
+        assert_eq!(m.iter().next(), Some((9, &vec![9; 100])));
+        let kept = (0..9).filter(|i| !m.entries[*i].t.is_empty()).count();
+        assert_eq!(kept, 2);
+        m.remove(9);
+        assert_eq!(m.free_len(), 9);
+        assert_eq!(m.entries.len(), 9);
+    }
+
+    #[test]
+    fn test_shrink_and_compact() {
+        let mut m = Slab::new();
+        for i in 0..100 {
+            m.insert(i);
+        }
+        for i in (0..100).filter(|i| i % 3 != 0) {
+            m.remove(i);
+        }
+        assert_eq!(m.len(), 34);
+        m.shrink_to(10);
+        assert_eq!(m.free_len(), 66);
+        let remap = m.compact();
+        assert_eq!(m.free_len(), 66);
+        for (old, new) in &remap {
+            assert!(old > new);
+            assert_eq!(m[new], *old);
+        }
+        m.shrink_to(10);
+        assert_eq!(m.free_len(), 10);
+        m.shrink_to(0);
+        assert_eq!(m.entries.len(), 34);
+        let mut values: Vec<_> = m.iter().map(|(_, v)| *v).collect();
+        values.sort();
+        assert_eq!(values, (0..100).filter(|i| i % 3 == 0).collect::<Vec<_>>());
+        assert_eq!(m.insert(1000), 34);
+    }
+
     #[test]
     fn test_drain() {
         let mut a = Slab::new();
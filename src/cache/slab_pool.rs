@@ -0,0 +1,348 @@
+// Copyright 2022 - 2024 Wenmeng See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// Author: tickbh
+// -----
+// Created Date: 2026/10/18 11:02:45
+
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    thread,
+};
+
+use super::Reinit;
+
+static NEXT_SHARD: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// 每个线程固定优先使用的分片, 减少线程间的锁竞争
+    static SHARD_INDEX: usize = NEXT_SHARD.fetch_add(1, Ordering::Relaxed);
+}
+
+struct PoolInner<T> {
+    shards: Vec<Mutex<Vec<Box<T>>>>,
+    /// 分片中可被取出的对象数量, 取出前先在此预留, 预留成功时分片中必定有对应的对象
+    avail: AtomicUsize,
+    /// 分片中存放及正在归还的对象数量, 用于限制空闲对象的上限
+    free: AtomicUsize,
+    used: AtomicUsize,
+    max_free: usize,
+}
+
+impl<T> PoolInner<T> {
+    /// 先预留一个空闲对象, 再优先从当前线程的分片中取出, 为空时从其它分片窃取
+    ///
+    /// 只有所有对象都在使用中时才返回None, 因此对象总数不超过同时使用的最大数量
+    fn take(&self) -> Option<Box<T>> {
+        self.avail
+            .fetch_update(Ordering::Acquire, Ordering::Relaxed, |v| v.checked_sub(1))
+            .ok()?;
+        let start = SHARD_INDEX.with(|i| *i);
+        loop {
+            for i in 0..self.shards.len() {
+                let shard = &self.shards[(start + i) % self.shards.len()];
+                if let Some(obj) = shard.lock().unwrap_or_else(PoisonError::into_inner).pop() {
+                    self.free.fetch_sub(1, Ordering::Relaxed);
+                    return Some(obj);
+                }
+            }
+            // 预留的对象正由其它线程放入分片, 稍后重新查找
+            thread::yield_now();
+        }
+    }
+
+    /// 归还对象到当前线程的分片, 空闲对象达到上限时直接释放
+    fn give_back(&self, obj: Box<T>) {
+        let max_free = self.max_free;
+        let reserved = self
+            .free
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |v| {
+                (v < max_free).then_some(v + 1)
+            });
+        if reserved.is_err() {
+            return;
+        }
+        let index = SHARD_INDEX.with(|i| *i) % self.shards.len();
+        self.shards[index]
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(obj);
+        self.avail.fetch_add(1, Ordering::Release);
+    }
+}
+
+/// 线程安全的对象池, 与[`crate::Slab`]一样缓存大对象以重复使用
+/// 获取到的对象由[`PoolGuard`]持有, 离开作用域时自动调用`reinit`并归还对象池
+/// 对象以`Box`存放, 在池中及使用中均不会移动地址
+/// 对象池可clone后发送给多个线程同时申请及归还
+/// 每个线程优先使用各自的分片, 分片为空时从其它分片窃取, 池中有空闲对象时不会新建
+///
+/// # Examples
+///
+/// ```
+/// use algorithm::SlabPool;
+/// use std::thread;
+/// fn main() {
+///     let pool = SlabPool::<Vec<u8>>::new();
+///     let handles: Vec<_> = (0..4).map(|_| {
+///         let pool = pool.clone();
+///         thread::spawn(move || {
+///             for i in 0..100 {
+///                 let mut buf = pool.get();
+///                 assert!(buf.is_empty());
+///                 buf.push(i);
+///             }
+///         })
+///     }).collect();
+///     for h in handles {
+///         h.join().unwrap();
+///     }
+///     assert!(pool.len() == 0);
+///     // 仅在所有对象都被使用时才新建, 空闲数量不超过同时使用的线程数
+///     let free = pool.free_len();
+///     assert!(free >= 1 && free <= 4);
+///     // 复用空闲对象
+///     drop(pool.get());
+///     assert!(pool.free_len() == free);
+/// }
+/// ```
+pub struct SlabPool<T: Default + Reinit> {
+    inner: Arc<PoolInner<T>>,
+}
+
+impl<T: Default + Reinit> SlabPool<T> {
+    /// 创建对象池, 分片数为cpu的并行数, 不限制空闲对象的数量
+    pub fn new() -> Self {
+        Self::with_max_free(usize::MAX)
+    }
+
+    /// 创建对象池, 空闲对象超过`max_free`时归还的对象将直接释放
+    ///
+    /// ```
+    /// use algorithm::SlabPool;
+    /// fn main() {
+    ///     let pool = SlabPool::<String>::with_max_free(1);
+    ///     let a = pool.get();
+    ///     let b = pool.get();
+    ///     drop(a);
+    ///     drop(b);
+    ///     assert!(pool.free_len() == 1);
+    /// }
+    /// ```
+    pub fn with_max_free(max_free: usize) -> Self {
+        let shards = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+        Self::with_shards(shards, max_free)
+    }
+
+    /// 创建指定分片数量的对象池
+    pub fn with_shards(shards: usize, max_free: usize) -> Self {
+        let shards = (0..shards.max(1)).map(|_| Mutex::new(Vec::new())).collect();
+        SlabPool {
+            inner: Arc::new(PoolInner {
+                shards,
+                avail: AtomicUsize::new(0),
+                free: AtomicUsize::new(0),
+                used: AtomicUsize::new(0),
+                max_free,
+            }),
+        }
+    }
+
+    /// 获取一个对象, 优先复用池中已初始化的对象, 否则通过Default新建
+    ///
+    /// ```
+    /// use algorithm::SlabPool;
+    /// fn main() {
+    ///     let pool = SlabPool::<String>::new();
+    ///     {
+    ///         let mut s = pool.get();
+    ///         s.push_str("slab");
+    ///         assert!(pool.len() == 1);
+    ///     }
+    ///     assert!(pool.len() == 0);
+    ///     assert!(pool.free_len() == 1);
+    ///     assert!(pool.get().as_str() == "");
+    /// }
+    /// ```
+    pub fn get(&self) -> PoolGuard<T> {
+        let obj = self.inner.take().unwrap_or_default();
+        self.inner.used.fetch_add(1, Ordering::Relaxed);
+        PoolGuard {
+            obj: Some(obj),
+            pool: self.inner.clone(),
+        }
+    }
+
+    /// 当前正在使用中的对象数量
+    pub fn len(&self) -> usize {
+        self.inner.used.load(Ordering::Relaxed)
+    }
+
+    /// 是否没有正在使用的对象
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 池中空闲待复用的对象数量
+    pub fn free_len(&self) -> usize {
+        self.inner.free.load(Ordering::Relaxed)
+    }
+
+    /// 释放池中所有的空闲对象
+    pub fn clear(&self) {
+        while self.inner.take().is_some() {}
+    }
+}
+
+impl<T: Default + Reinit> Default for SlabPool<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Default + Reinit> Clone for SlabPool<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: Default + Reinit> fmt::Debug for SlabPool<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlabPool")
+            .field("len", &self.len())
+            .field("free_len", &self.free_len())
+            .finish()
+    }
+}
+
+/// 对象池中取出的对象, 析构时重新初始化并归还对象池
+pub struct PoolGuard<T: Default + Reinit> {
+    obj: Option<Box<T>>,
+    pool: Arc<PoolInner<T>>,
+}
+
+impl<T: Default + Reinit> PoolGuard<T> {
+    /// 取出对象的所有权, 该对象将不再归还对象池
+    ///
+    /// ```
+    /// use algorithm::{PoolGuard, SlabPool};
+    /// fn main() {
+    ///     let pool = SlabPool::<String>::new();
+    ///     let s = PoolGuard::into_inner(pool.get());
+    ///     assert!(pool.len() == 0);
+    ///     assert!(pool.free_len() == 0);
+    ///     drop(s);
+    /// }
+    /// ```
+    pub fn into_inner(mut guard: Self) -> T {
+        guard.pool.used.fetch_sub(1, Ordering::Relaxed);
+        *guard.obj.take().expect("object taken")
+    }
+}
+
+impl<T: Default + Reinit> Deref for PoolGuard<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        self.obj.as_ref().expect("object taken")
+    }
+}
+
+impl<T: Default + Reinit> DerefMut for PoolGuard<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        self.obj.as_mut().expect("object taken")
+    }
+}
+
+impl<T: Default + Reinit + fmt::Debug> fmt::Debug for PoolGuard<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: Default + Reinit> Drop for PoolGuard<T> {
+    fn drop(&mut self) {
+        if let Some(mut obj) = self.obj.take() {
+            self.pool.used.fetch_sub(1, Ordering::Relaxed);
+            obj.reinit();
+            self.pool.give_back(obj);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::SlabPool;
+
+    #[test]
+    fn test_reuse() {
+        let pool = SlabPool::<Vec<usize>>::with_shards(1, usize::MAX);
+        let addr = {
+            let mut v = pool.get();
+            v.extend(0..100);
+            v.as_ptr()
+        };
+        let v = pool.get();
+        assert!(v.is_empty());
+        assert!(v.capacity() >= 100);
+        assert_eq!(v.as_ptr(), addr);
+    }
+
+    #[test]
+    fn test_max_free() {
+        let pool = SlabPool::<u64>::with_shards(2, 3);
+        let guards: Vec<_> = (0..10).map(|_| pool.get()).collect();
+        assert_eq!(pool.len(), 10);
+        drop(guards);
+        assert_eq!(pool.len(), 0);
+        assert_eq!(pool.free_len(), 3);
+        pool.clear();
+        assert_eq!(pool.free_len(), 0);
+    }
+
+    #[test]
+    fn test_threads() {
+        let pool = SlabPool::<String>::with_shards(4, usize::MAX);
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let pool = pool.clone();
+                thread::spawn(move || {
+                    for j in 0..1000 {
+                        let mut s = pool.get();
+                        assert!(s.is_empty());
+                        s.push_str(&format!("{}-{}", i, j));
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+        assert!(pool.is_empty());
+        // 当前线程的分片为空时从其它分片窃取, 对象总数不超过线程数
+        let free = pool.free_len();
+        assert!(free >= 1 && free <= 8);
+        let guards: Vec<_> = (0..free).map(|_| pool.get()).collect();
+        assert_eq!(pool.free_len(), 0);
+        drop(guards);
+        assert_eq!(pool.free_len(), free);
+    }
+}
@@ -6,15 +6,31 @@ pub mod buf;
 mod cache;
 mod key;
 mod map;
+mod ratelimit;
 mod timer;
 mod tree;
 mod util;
 
-pub use arr::{CircularBuffer, FixedVec, SkipList, SkipNode};
-pub use cache::{ArcCache, LfuCache, LruCache, LruKCache, Reinit, Slab};
+pub use arr::{CircularBuffer, FixedVec, SkipList, SkipNode, SlidingWindow};
+pub use cache::{
+    ArcCache, GenKey, GenSlab, LfuCache, LruCache, LruKCache, PageSlab, PoolGuard, Reinit, Slab,
+    SlabPool,
+};
 pub use key::{KeyRef, KeyWrapper};
 pub use map::{BitMap, RoaringBitMap, ZSet};
-pub use timer::{StampTimer, StepTimer, Timer, TimerRBTree, TimerWheel};
+pub use ratelimit::{
+    FixedWindow, KeyedFixedWindow, KeyedLeakyBucket, KeyedLimiter, KeyedSlidingWindowCounter,
+    KeyedSlidingWindowLog, KeyedTokenBucket, LeakyBucket, Limiter, RetryAfter,
+    SlidingWindowCounter, SlidingWindowLog, TokenBucket,
+};
+pub use timer::{
+    AsyncTimer, CronError, CronSchedule, CronTimer, DelayKey, DelayQueue, DrainExpired, Elapsed,
+    InstantSource, Interval, PendingRepeat, PendingTimer, Pop, Sleep, StampTimer, StepTimer,
+    TickSource, Timeout, Timer, TimerHandle, TimerQueue, TimerRBTree, TimerService, TimerWheel,
+    TimerWheelBuilder, TimerWheelError,
+};
+#[cfg(feature = "sim")]
+pub use timer::{SimClock, SimEvent, SimHandle, SimTimer};
 pub use tree::RBTree;
 pub use util::*;
 
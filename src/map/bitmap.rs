@@ -1,4 +1,4 @@
-use std::{fmt::{Debug, Display}, ops::{BitAnd, BitOr, BitXor}};
+use std::{fmt::{Debug, Display}, ops::{BitAnd, BitOr, BitXor, Bound, RangeBounds}};
 
 /// 位图类，根据访问的位看是否被占用
 /// 解决经典的是否被占用的问题，但是相对占用大小会较大
@@ -40,6 +40,26 @@ impl BitMap {
         }
     }
 
+    /// 由按位小端排列的字节构造位图
+    pub(crate) fn from_bytes(entries: Vec<u8>) -> Self {
+        let mut map = Self {
+            cap: entries.len() * 8,
+            len: entries.iter().map(|v| v.count_ones() as usize).sum(),
+            entries,
+            max_key: 0,
+            min_key: 0,
+        };
+        // max按max_key限定扫描范围, 需先从最后一位开始扫描
+        map.max_key = map.cap.saturating_sub(1);
+        map.max_key = map.max().unwrap_or(0);
+        map
+    }
+
+    /// 位图底层的字节, 第`i`位存储于第`i / 8`个字节的第`i % 8`位
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        &self.entries
+    }
+
     pub fn capacity(&self) -> usize {
         self.cap
     }
@@ -240,6 +260,153 @@ impl BitMap {
             base: self,
             len: self.len,
             val: self.min_key,
+            end: self.max_key,
+        }
+    }
+
+    /// 最小的元素
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::BitMap;
+    /// fn main() {
+    ///     let mut map = BitMap::new(10240);
+    ///     assert_eq!(map.min(), None);
+    ///     map.add_many(&vec![30, 9, 100]);
+    ///     assert_eq!(map.min(), Some(9));
+    ///     map.remove(9);
+    ///     assert_eq!(map.min(), Some(30));
+    /// }
+    /// ```
+    pub fn min(&self) -> Option<usize> {
+        if self.len == 0 {
+            return None;
+        }
+        let pos = (self.min_key / 8..self.entries.len()).find(|&pos| self.entries[pos] != 0)?;
+        Some(pos * 8 + self.entries[pos].trailing_zeros() as usize)
+    }
+
+    /// 最大的元素
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::BitMap;
+    /// fn main() {
+    ///     let mut map = BitMap::new(10240);
+    ///     assert_eq!(map.max(), None);
+    ///     map.add_many(&vec![30, 9, 100]);
+    ///     assert_eq!(map.max(), Some(100));
+    ///     map.remove(100);
+    ///     assert_eq!(map.max(), Some(30));
+    /// }
+    /// ```
+    pub fn max(&self) -> Option<usize> {
+        if self.len == 0 {
+            return None;
+        }
+        let last = (self.max_key / 8).min(self.entries.len().saturating_sub(1));
+        let pos = (0..=last).rev().find(|&pos| self.entries[pos] != 0)?;
+        Some(pos * 8 + 7 - self.entries[pos].leading_zeros() as usize)
+    }
+
+    /// 小于等于`val`的元素个数
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::BitMap;
+    /// fn main() {
+    ///     let mut map = BitMap::new(10240);
+    ///     map.add_many(&vec![1, 9, 10, 100]);
+    ///     assert_eq!(map.rank(0), 0);
+    ///     assert_eq!(map.rank(9), 2);
+    ///     assert_eq!(map.rank(99), 3);
+    ///     assert_eq!(map.rank(100000), 4);
+    /// }
+    /// ```
+    pub fn rank(&self, val: usize) -> usize {
+        let pos = val / 8;
+        if pos >= self.entries.len() {
+            return self.len;
+        }
+        let full: usize = self.entries[..pos].iter().map(|v| v.count_ones() as usize).sum();
+        let mask = ((1u16 << (val % 8 + 1)) - 1) as u8;
+        full + (self.entries[pos] & mask).count_ones() as usize
+    }
+
+    /// 从小到大第`n`个元素(从0开始), 与[`BitMap::rank`]互逆, 可用于分页
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::BitMap;
+    /// fn main() {
+    ///     let mut map = BitMap::new(10240);
+    ///     map.add_many(&vec![1, 9, 10, 100]);
+    ///     assert_eq!(map.select(0), Some(1));
+    ///     assert_eq!(map.select(3), Some(100));
+    ///     assert_eq!(map.select(4), None);
+    ///     assert_eq!(map.rank(map.select(2).unwrap()), 3);
+    /// }
+    /// ```
+    pub fn select(&self, n: usize) -> Option<usize> {
+        if n >= self.len {
+            return None;
+        }
+        let mut remain = n;
+        for (pos, &byte) in self.entries.iter().enumerate() {
+            let ones = byte.count_ones() as usize;
+            if remain < ones {
+                let mut byte = byte;
+                for _ in 0..remain {
+                    byte &= byte - 1;
+                }
+                return Some(pos * 8 + byte.trailing_zeros() as usize);
+            }
+            remain -= ones;
+        }
+        None
+    }
+
+    /// 范围内的元素个数
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::BitMap;
+    /// fn main() {
+    ///     let mut map = BitMap::new(10240);
+    ///     map.add_range(10, 19);
+    ///     assert_eq!(map.range_cardinality(12..15), 3);
+    ///     assert_eq!(map.range_cardinality(..=12), 3);
+    ///     assert_eq!(map.range_cardinality(15..), 5);
+    ///     assert_eq!(map.range_cardinality(15..15), 0);
+    /// }
+    /// ```
+    pub fn range_cardinality<R: RangeBounds<usize>>(&self, range: R) -> usize {
+        match range_inclusive(&range) {
+            Some((start, end)) if start > 0 => self.rank(end) - self.rank(start - 1),
+            Some((_, end)) => self.rank(end),
+            None => 0,
+        }
+    }
+
+    /// 范围内元素的迭代器
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::BitMap;
+    /// fn main() {
+    ///     let mut map = BitMap::new(10240);
+    ///     map.add_many(&vec![1, 9, 10, 100, 200]);
+    ///     assert_eq!(map.iter_range(5..=100).collect::<Vec<_>>(), vec![9, 10, 100]);
+    ///     assert_eq!(map.iter_range(10..).rev().collect::<Vec<_>>(), vec![200, 100, 10]);
+    /// }
+    /// ```
+    pub fn iter_range<R: RangeBounds<usize>>(&self, range: R) -> Iter<'_> {
+        let (val, end) = range_inclusive(&range).unwrap_or((1, 0));
+        Iter {
+            base: self,
+            len: self.range_cardinality(range),
+            val,
+            end: end.min(self.max_key),
         }
     }
 
@@ -443,6 +610,8 @@ pub struct Iter<'a> {
     base: &'a BitMap,
     len: usize,
     val: usize,
+    /// 向后迭代的位置(包含)
+    end: usize,
 }
 
 impl<'a> Iterator for Iter<'a> {
@@ -453,7 +622,7 @@ impl<'a> Iterator for Iter<'a> {
             return None;
         }
 
-        for i in self.val..=self.base.max_key {
+        for i in self.val..=self.end {
             if self.base.contains(&i) {
                 self.len -= 1;
                 self.val = i + 1;
@@ -473,10 +642,10 @@ impl<'a> DoubleEndedIterator for Iter<'a> {
         if self.len == 0 {
             return None;
         }
-        for i in (0..=(self.base.max_key - self.val)).rev() {
+        for i in (self.val..=self.end).rev() {
             if self.base.contains(&i) {
                 self.len -= 1;
-                self.val = self.base.max_key - i;
+                self.end = i.saturating_sub(1);
                 return Some(i);
             }
         }
@@ -484,6 +653,25 @@ impl<'a> DoubleEndedIterator for Iter<'a> {
     }
 }
 
+/// 将范围转换成包含头与尾的区间, 范围为空时返回None
+pub(crate) fn range_inclusive<R: RangeBounds<usize>>(range: &R) -> Option<(usize, usize)> {
+    let start = match range.start_bound() {
+        Bound::Included(&v) => v,
+        Bound::Excluded(&v) => v.checked_add(1)?,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&v) => v,
+        Bound::Excluded(&v) => v.checked_sub(1)?,
+        Bound::Unbounded => usize::MAX,
+    };
+    if start > end {
+        None
+    } else {
+        Some((start, end))
+    }
+}
+
 impl Display for BitMap {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!("len:{}-val:{{", self.len))?;
@@ -522,4 +710,23 @@ mod tests {
         m.add_many(&vec![1, 3, 9]);
         assert_eq!(format!("{}", m), "len:3-val:{1,3,9}".to_string());
     }
+
+    #[test]
+    fn test_rank_select() {
+        let mut m = BitMap::new(1000);
+        let vals: Vec<usize> = (0..1000).filter(|v| v % 7 == 3 || v % 11 == 0).collect();
+        m.add_many(&vals);
+        assert_eq!(m.iter().rev().collect::<Vec<_>>(), vals.iter().rev().copied().collect::<Vec<_>>());
+        assert_eq!(m.min(), Some(0));
+        assert_eq!(m.max(), vals.last().copied());
+        for (n, v) in vals.iter().enumerate() {
+            assert_eq!(m.select(n), Some(*v));
+            assert_eq!(m.rank(*v), n + 1);
+        }
+        for (a, b) in [(0, 999), (5, 17), (17, 5), (300, 300), (990, 2000)] {
+            let expect: Vec<usize> = vals.iter().copied().filter(|v| *v >= a && *v <= b).collect();
+            assert_eq!(m.range_cardinality(a..=b), expect.len());
+            assert_eq!(m.iter_range(a..=b).collect::<Vec<_>>(), expect);
+        }
+    }
 }
\ No newline at end of file
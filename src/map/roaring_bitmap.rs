@@ -1,17 +1,45 @@
 use std::collections::HashMap;
 use std::fmt::{Debug, Display};
-use std::ops::{BitAnd, BitOr, BitXor};
+use std::io::{self, Read, Write};
+use std::ops::{BitAnd, BitOr, BitXor, RangeBounds};
 
 use crate::BitMap;
 
+use super::bitmap::range_inclusive;
+
 
 const TAIL_BIT: usize = 16;
 const TAIL_NUM: usize = 0x10000;
+/// 数组容器的最大元素个数, 超过时以位图存储
+const ARRAY_LIMIT: usize = 4096;
+/// 位图容器序列化后的字节数
+const BITMAP_BYTES: usize = TAIL_NUM / 8;
+
+const SERIAL_COOKIE_NO_RUNCONTAINER: u32 = 12346;
+const SERIAL_COOKIE: u32 = 12347;
+/// 含有Run容器时, 容器数小于该值则不写入偏移量
+const NO_OFFSET_THRESHOLD: usize = 4;
 
 #[derive(Clone)]
 enum TailContainer {
     Array(Vec<u16>),
     Bitmap(BitMap),
+    /// 有序且互不相邻的连续区间(包含头与尾)
+    Run(Vec<(u16, u16)>),
+}
+
+/// Run容器序列化后的字节数
+fn run_size(runs: usize) -> usize {
+    2 + runs * 4
+}
+
+/// 数组或位图容器中较小的字节数
+fn plain_size(len: usize) -> usize {
+    if len <= ARRAY_LIMIT {
+        len * 2
+    } else {
+        BITMAP_BYTES
+    }
 }
 
 impl TailContainer {
@@ -21,7 +49,7 @@ impl TailContainer {
 
     fn try_move(&mut self) {
         let val = match self {
-            TailContainer::Array(v) if v.len() >= 4096 => {
+            TailContainer::Array(v) if v.len() >= ARRAY_LIMIT => {
                 v.drain(..).collect::<Vec<_>>()
             }
             _ => {
@@ -36,6 +64,74 @@ impl TailContainer {
         *self = TailContainer::Bitmap(map);
     }
 
+    /// 位图元素不超过4096个时转回数组
+    fn try_move_back(&mut self) {
+        if let TailContainer::Bitmap(hash) = self {
+            if hash.len() <= ARRAY_LIMIT {
+                *self = TailContainer::Array(hash.iter().map(|v| v as u16).collect());
+            }
+        }
+    }
+
+    /// Run容器的区间过多不再节省空间时转为数组或位图
+    fn try_move_run(&mut self) {
+        let len = self.len();
+        if let TailContainer::Run(runs) = self {
+            if run_size(runs.len()) >= plain_size(len) {
+                let runs = std::mem::take(runs);
+                *self = Self::from_runs(runs);
+            }
+        }
+    }
+
+    /// 由区间构造占用空间最小的容器
+    fn from_runs(runs: Vec<(u16, u16)>) -> Self {
+        let len = runs.iter().map(|(s, e)| (e - s) as usize + 1).sum::<usize>();
+        if run_size(runs.len()) < plain_size(len) {
+            TailContainer::Run(runs)
+        } else if len <= ARRAY_LIMIT {
+            TailContainer::Array(runs.into_iter().flat_map(|(s, e)| s..=e).collect())
+        } else {
+            let mut map = BitMap::new(TAIL_NUM);
+            for (s, e) in runs {
+                map.add_range(s as usize, e as usize);
+            }
+            TailContainer::Bitmap(map)
+        }
+    }
+
+    fn to_runs(&self) -> Vec<(u16, u16)> {
+        let mut runs: Vec<(u16, u16)> = vec![];
+        let mut push = |v: u16| match runs.last_mut() {
+            Some(last) if last.1 + 1 == v => last.1 = v,
+            _ => runs.push((v, v)),
+        };
+        match self {
+            TailContainer::Array(vec) => vec.iter().for_each(|v| push(*v)),
+            TailContainer::Bitmap(hash) => hash.iter().for_each(|v| push(v as u16)),
+            TailContainer::Run(runs) => return runs.clone(),
+        }
+        runs
+    }
+
+    /// 改为占用空间最小的容器, 有变化时返回true
+    fn optimize(&mut self) -> bool {
+        let best = Self::from_runs(self.to_runs());
+        if std::mem::discriminant(self) == std::mem::discriminant(&best) {
+            return false;
+        }
+        *self = best;
+        true
+    }
+
+    fn shrink_to_fit(&mut self) {
+        match self {
+            TailContainer::Array(vec) => vec.shrink_to_fit(),
+            TailContainer::Bitmap(_) => {}
+            TailContainer::Run(runs) => runs.shrink_to_fit(),
+        }
+    }
+
     pub fn add(&mut self, val: u16) -> bool {
         self.try_move();
         match self {
@@ -47,12 +143,31 @@ impl TailContainer {
                     false
                 }
             },
-            TailContainer::Bitmap(hash) => hash.add(val as usize)
+            TailContainer::Bitmap(hash) => hash.add(val as usize),
+            TailContainer::Run(runs) => {
+                let idx = runs.partition_point(|r| r.0 <= val);
+                if idx > 0 && runs[idx - 1].1 >= val {
+                    return false;
+                }
+                let join_left = idx > 0 && runs[idx - 1].1 + 1 == val;
+                let join_right = idx < runs.len() && runs[idx].0 == val + 1;
+                match (join_left, join_right) {
+                    (true, true) => {
+                        runs[idx - 1].1 = runs[idx].1;
+                        runs.remove(idx);
+                    }
+                    (true, false) => runs[idx - 1].1 = val,
+                    (false, true) => runs[idx].0 = val,
+                    (false, false) => runs.insert(idx, (val, val)),
+                }
+                self.try_move_run();
+                true
+            }
         }
     }
 
     pub fn remove(&mut self, val: u16) -> bool {
-        match self {
+        let success = match self {
             TailContainer::Array(vec) => {
                 if let Ok(s) = vec.binary_search(&val) {
                     vec.remove(s);
@@ -61,8 +176,72 @@ impl TailContainer {
                     false
                 }
             },
-            TailContainer::Bitmap(hash) => hash.remove(val as usize)
+            TailContainer::Bitmap(hash) => hash.remove(val as usize),
+            TailContainer::Run(runs) => {
+                let idx = runs.partition_point(|r| r.0 <= val);
+                if idx == 0 || runs[idx - 1].1 < val {
+                    return false;
+                }
+                let (start, end) = runs[idx - 1];
+                if start == end {
+                    runs.remove(idx - 1);
+                } else if start == val {
+                    runs[idx - 1].0 = val + 1;
+                } else if end == val {
+                    runs[idx - 1].1 = val - 1;
+                } else {
+                    runs[idx - 1].1 = val - 1;
+                    runs.insert(idx, (val + 1, end));
+                }
+                true
+            }
+        };
+        if success {
+            self.try_move_back();
+            self.try_move_run();
+        }
+        success
+    }
+
+    /// 添加区间内的元素(包含头与尾)
+    pub fn add_range(&mut self, start: u16, end: u16) {
+        if let TailContainer::Bitmap(hash) = self {
+            hash.add_range(start as usize, end as usize);
+            return;
+        }
+        let mut runs = self.to_runs();
+        // 与新区间重叠或相邻的区间
+        let from = runs.partition_point(|r| (r.1 as usize) + 1 < start as usize);
+        let to = runs.partition_point(|r| r.0 as usize <= end as usize + 1);
+        let mut merged = (start, end);
+        if from < to {
+            merged = (start.min(runs[from].0), end.max(runs[to - 1].1));
         }
+        runs.splice(from..to, [merged]);
+        *self = Self::from_runs(runs);
+    }
+
+    /// 删除区间内的元素(包含头与尾)
+    pub fn remove_range(&mut self, start: u16, end: u16) {
+        if let TailContainer::Bitmap(hash) = self {
+            hash.remove_range(start as usize, end as usize);
+            self.try_move_back();
+            return;
+        }
+        let mut runs = self.to_runs();
+        let from = runs.partition_point(|r| r.1 < start);
+        let to = runs.partition_point(|r| r.0 <= end);
+        if from < to {
+            let mut remain = vec![];
+            if runs[from].0 < start {
+                remain.push((runs[from].0, start - 1));
+            }
+            if runs[to - 1].1 > end {
+                remain.push((end + 1, runs[to - 1].1));
+            }
+            runs.splice(from..to, remain);
+        }
+        *self = Self::from_runs(runs);
     }
 
     pub fn next(&self, val: u16) -> Option<u16> {
@@ -86,6 +265,10 @@ impl TailContainer {
                 }
                 return None;
             }
+            TailContainer::Run(runs) => {
+                let idx = runs.partition_point(|r| r.1 < val);
+                runs.get(idx).map(|r| r.0.max(val))
+            }
         }
     }
 
@@ -111,9 +294,149 @@ impl TailContainer {
                 }
                 return None;
             }
+            TailContainer::Run(runs) => {
+                let idx = runs.partition_point(|r| r.0 <= val);
+                if idx == 0 {
+                    None
+                } else {
+                    Some(runs[idx - 1].1.min(val))
+                }
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            TailContainer::Array(vec) => vec.len(),
+            TailContainer::Bitmap(hash) => hash.len(),
+            TailContainer::Run(runs) => runs.iter().map(|(s, e)| (e - s) as usize + 1).sum(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self {
+            TailContainer::Array(vec) => vec.is_empty(),
+            TailContainer::Bitmap(hash) => hash.is_empty(),
+            TailContainer::Run(runs) => runs.is_empty(),
         }
     }
 
+    pub fn min(&self) -> Option<u16> {
+        match self {
+            TailContainer::Array(vec) => vec.first().copied(),
+            TailContainer::Bitmap(hash) => hash.min().map(|v| v as u16),
+            TailContainer::Run(runs) => runs.first().map(|r| r.0),
+        }
+    }
+
+    pub fn max(&self) -> Option<u16> {
+        match self {
+            TailContainer::Array(vec) => vec.last().copied(),
+            TailContainer::Bitmap(hash) => hash.max().map(|v| v as u16),
+            TailContainer::Run(runs) => runs.last().map(|r| r.1),
+        }
+    }
+
+    /// 小于等于`val`的元素个数
+    pub fn rank(&self, val: u16) -> usize {
+        match self {
+            TailContainer::Array(vec) => vec.partition_point(|v| *v <= val),
+            TailContainer::Bitmap(hash) => hash.rank(val as usize),
+            TailContainer::Run(runs) => runs
+                .iter()
+                .take_while(|r| r.0 <= val)
+                .map(|(s, e)| (*e.min(&val) - s) as usize + 1)
+                .sum(),
+        }
+    }
+
+    pub fn select(&self, n: usize) -> Option<u16> {
+        match self {
+            TailContainer::Array(vec) => vec.get(n).copied(),
+            TailContainer::Bitmap(hash) => hash.select(n).map(|v| v as u16),
+            TailContainer::Run(runs) => {
+                let mut remain = n;
+                for (s, e) in runs {
+                    let len = (e - s) as usize + 1;
+                    if remain < len {
+                        return Some(s + remain as u16);
+                    }
+                    remain -= len;
+                }
+                None
+            }
+        }
+    }
+
+    /// 按RoaringFormatSpec序列化后的字节数, 非Run容器元素个数不超过4096时以数组存储, 否则以位图存储
+    fn serialized_size(&self) -> usize {
+        match self {
+            TailContainer::Run(runs) => run_size(runs.len()),
+            _ => plain_size(self.len()),
+        }
+    }
+
+    fn serialize_into<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            TailContainer::Array(vec) => {
+                for v in vec {
+                    writer.write_all(&v.to_le_bytes())?;
+                }
+            }
+            TailContainer::Bitmap(hash) if hash.len() <= ARRAY_LIMIT => {
+                for v in hash.iter() {
+                    writer.write_all(&(v as u16).to_le_bytes())?;
+                }
+            }
+            TailContainer::Bitmap(hash) => writer.write_all(hash.as_bytes())?,
+            TailContainer::Run(runs) => {
+                writer.write_all(&(runs.len() as u16).to_le_bytes())?;
+                for (s, e) in runs {
+                    writer.write_all(&s.to_le_bytes())?;
+                    writer.write_all(&(e - s).to_le_bytes())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn deserialize_from<R: Read>(reader: &mut R, len: usize, is_run: bool) -> io::Result<Self> {
+        let container = if is_run {
+            let mut runs: Vec<(u16, u16)> = vec![];
+            for _ in 0..read_u16(reader)? {
+                let start = read_u16(reader)? as usize;
+                let end = start + read_u16(reader)? as usize;
+                match runs.last_mut() {
+                    _ if end >= TAIL_NUM => return Err(invalid_data("invalid run container")),
+                    Some(last) if last.1 as usize >= start => {
+                        return Err(invalid_data("invalid run container"))
+                    }
+                    Some(last) if last.1 as usize + 1 == start => last.1 = end as u16,
+                    _ => runs.push((start as u16, end as u16)),
+                }
+            }
+            TailContainer::Run(runs)
+        } else if len <= ARRAY_LIMIT {
+            let mut vals = Vec::with_capacity(len);
+            for _ in 0..len {
+                let v = read_u16(reader)?;
+                if vals.last().is_some_and(|last| *last >= v) {
+                    return Err(invalid_data("unsorted array container"));
+                }
+                vals.push(v);
+            }
+            TailContainer::Array(vals)
+        } else {
+            let mut entries = vec![0; BITMAP_BYTES];
+            reader.read_exact(&mut entries)?;
+            TailContainer::Bitmap(BitMap::from_bytes(entries))
+        };
+        if container.len() != len {
+            return Err(invalid_data("container cardinality mismatch"));
+        }
+        Ok(container)
+    }
+
     pub fn contains(&self, val: u16) -> bool {
         match self {
             TailContainer::Array(vec) => {
@@ -123,7 +446,11 @@ impl TailContainer {
                     false
                 }
             },
-            TailContainer::Bitmap(hash) => hash.contains(&(val as usize))
+            TailContainer::Bitmap(hash) => hash.contains(&(val as usize)),
+            TailContainer::Run(runs) => {
+                let idx = runs.partition_point(|r| r.0 <= val);
+                idx > 0 && runs[idx - 1].1 >= val
+            }
         }
     }
 }
@@ -131,8 +458,9 @@ impl TailContainer {
 /// 位图类RoaringBitMap，根据访问的位看是否被占用
 /// 本质上是将大块的bitmap分成各个小块，其中每个小块在需要存储数据的时候才会存在
 /// 解决经典的是否被占用的问题，不会一次性分配大内存
-/// 头部以val / 65536做为索引键值, 尾部分为Array及HashSet结构
-/// 当元素个数小于4096时以有序array做为索引, 当>4096以HashSet做为存储
+/// 头部以val / 65536做为索引键值, 尾部分为Array, Bitmap及Run结构
+/// 当元素个数不超过4096时以有序array做为索引, 当>4096以Bitmap做为存储,
+/// 连续的区间以Run存储, 可由[`RoaringBitMap::optimize`]为每个小块选择占用空间最小的结构
 ///
 /// # Examples
 ///
@@ -220,7 +548,7 @@ impl RoaringBitMap {
         }
     }
 
-    /// 添加范围内的元素(包含头与结果)，批量添加增加效率
+    /// 添加范围内的元素(包含头与结果)，批量添加增加效率, 连续的区间以Run结构存储
     /// # Examples
     ///
     /// ```
@@ -233,12 +561,30 @@ impl RoaringBitMap {
     ///     assert!(map.contains(&16));
     ///     assert!(!map.contains(&17));
     ///     assert!(map.len() == 10);
+    ///     map.add_range(0, 10_000_000);
+    ///     assert!(map.len() == 10_000_001);
     /// }
     /// ```
     pub fn add_range(&mut self, start: usize, end: usize) {
-        for i in start..=end {
-            self.add(i);
+        if start > end {
+            return;
         }
+        for head in (start >> TAIL_BIT)..=(end >> TAIL_BIT) {
+            let (from, to) = Self::chunk_range(head, start, end);
+            let container = self.map.entry(head).or_insert(TailContainer::new());
+            let before = container.len();
+            container.add_range(from, to);
+            self.len += container.len() - before;
+        }
+        self.min_key = self.min_key.min(start);
+        self.max_key = self.max_key.max(end);
+    }
+
+    /// 区间落在头部为`head`的小块中的部分
+    fn chunk_range(head: usize, start: usize, end: usize) -> (u16, u16) {
+        let from = if head == start >> TAIL_BIT { start % TAIL_NUM } else { 0 };
+        let to = if head == end >> TAIL_BIT { end % TAIL_NUM } else { TAIL_NUM - 1 };
+        (from as u16, to as u16)
     }
 
     /// 删除元素
@@ -262,6 +608,9 @@ impl RoaringBitMap {
         if let Some(map) = self.map.get_mut(&head) {
             if map.remove(tail) {
                 self.len -= 1;
+                if map.is_empty() {
+                    self.map.remove(&head);
+                }
                 return true;
             }
         }
@@ -304,9 +653,73 @@ impl RoaringBitMap {
     /// }
     /// ```
     pub fn remove_range(&mut self, start: usize, end: usize) {
-        for i in start..=end {
-            self.remove(i);
+        if start > end {
+            return;
         }
+        let mut heads: Vec<usize> = self
+            .map
+            .keys()
+            .copied()
+            .filter(|h| (start >> TAIL_BIT..=end >> TAIL_BIT).contains(h))
+            .collect();
+        heads.sort_unstable();
+        for head in heads {
+            let (from, to) = Self::chunk_range(head, start, end);
+            let container = self.map.get_mut(&head).expect("ok");
+            let before = container.len();
+            container.remove_range(from, to);
+            self.len -= before - container.len();
+            if container.is_empty() {
+                self.map.remove(&head);
+            }
+        }
+    }
+
+    /// 为每个小块选择序列化后占用空间最小的结构, 有结构发生变化时返回true
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::RoaringBitMap;
+    /// fn main() {
+    ///     let mut map = RoaringBitMap::new();
+    ///     for i in 0..10000 {
+    ///         map.add(i);
+    ///     }
+    ///     let size = map.serialized_size();
+    ///     assert!(map.optimize());
+    ///     assert!(map.serialized_size() < size);
+    ///     assert!(!map.optimize());
+    ///     assert_eq!(map.len(), 10000);
+    /// }
+    /// ```
+    pub fn optimize(&mut self) -> bool {
+        self.map.retain(|_, c| !c.is_empty());
+        let mut changed = false;
+        for container in self.map.values_mut() {
+            changed |= container.optimize();
+        }
+        changed
+    }
+
+    /// 在[`RoaringBitMap::optimize`]的基础上释放多余的内存
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::RoaringBitMap;
+    /// fn main() {
+    ///     let mut map = RoaringBitMap::new();
+    ///     map.add_range(0, 100000);
+    ///     map.remove_range(10, 99990);
+    ///     map.shrink_to_fit();
+    ///     assert_eq!(map.iter().count(), 20);
+    /// }
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.optimize();
+        for container in self.map.values_mut() {
+            container.shrink_to_fit();
+        }
+        self.map.shrink_to_fit();
     }
 
     /// 醒看是否包含
@@ -353,6 +766,309 @@ impl RoaringBitMap {
     }
 
 
+    /// 按头部从小到大排列的非空容器
+    fn sorted_containers(&self) -> Vec<(usize, &TailContainer)> {
+        let mut containers: Vec<_> = self
+            .map
+            .iter()
+            .filter(|(_, c)| !c.is_empty())
+            .map(|(h, c)| (*h, c))
+            .collect();
+        containers.sort_unstable_by_key(|(h, _)| *h);
+        containers
+    }
+
+    /// 最小的元素
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::RoaringBitMap;
+    /// fn main() {
+    ///     let mut map = RoaringBitMap::new();
+    ///     assert_eq!(map.min(), None);
+    ///     map.add_many(&vec![300000, 9, 100]);
+    ///     assert_eq!(map.min(), Some(9));
+    ///     map.remove(9);
+    ///     assert_eq!(map.min(), Some(100));
+    /// }
+    /// ```
+    pub fn min(&self) -> Option<usize> {
+        self.map
+            .iter()
+            .filter_map(|(h, c)| c.min().map(|t| h * TAIL_NUM + t as usize))
+            .min()
+    }
+
+    /// 最大的元素
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::RoaringBitMap;
+    /// fn main() {
+    ///     let mut map = RoaringBitMap::new();
+    ///     assert_eq!(map.max(), None);
+    ///     map.add_many(&vec![300000, 9, 100]);
+    ///     assert_eq!(map.max(), Some(300000));
+    ///     map.remove(300000);
+    ///     assert_eq!(map.max(), Some(100));
+    /// }
+    /// ```
+    pub fn max(&self) -> Option<usize> {
+        self.map
+            .iter()
+            .filter_map(|(h, c)| c.max().map(|t| h * TAIL_NUM + t as usize))
+            .max()
+    }
+
+    /// 小于等于`val`的元素个数
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::RoaringBitMap;
+    /// fn main() {
+    ///     let mut map = RoaringBitMap::new();
+    ///     map.add_many(&vec![1, 9, 10, 300000]);
+    ///     assert_eq!(map.rank(0), 0);
+    ///     assert_eq!(map.rank(9), 2);
+    ///     assert_eq!(map.rank(299999), 3);
+    ///     assert_eq!(map.rank(usize::MAX), 4);
+    /// }
+    /// ```
+    pub fn rank(&self, val: usize) -> usize {
+        let head = val >> TAIL_BIT;
+        let tail = (val % TAIL_NUM) as u16;
+        self.map
+            .iter()
+            .map(|(h, c)| {
+                if *h < head {
+                    c.len()
+                } else if *h == head {
+                    c.rank(tail)
+                } else {
+                    0
+                }
+            })
+            .sum()
+    }
+
+    /// 从小到大第`n`个元素(从0开始), 与[`RoaringBitMap::rank`]互逆, 可用于分页
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::RoaringBitMap;
+    /// fn main() {
+    ///     let mut map = RoaringBitMap::new();
+    ///     map.add_many(&vec![1, 9, 10, 300000]);
+    ///     assert_eq!(map.select(0), Some(1));
+    ///     assert_eq!(map.select(3), Some(300000));
+    ///     assert_eq!(map.select(4), None);
+    ///     assert_eq!(map.rank(map.select(2).unwrap()), 3);
+    /// }
+    /// ```
+    pub fn select(&self, n: usize) -> Option<usize> {
+        if n >= self.len {
+            return None;
+        }
+        let mut remain = n;
+        for (head, container) in self.sorted_containers() {
+            let len = container.len();
+            if remain < len {
+                return container.select(remain).map(|t| head * TAIL_NUM + t as usize);
+            }
+            remain -= len;
+        }
+        None
+    }
+
+    /// 范围内的元素个数
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::RoaringBitMap;
+    /// fn main() {
+    ///     let mut map = RoaringBitMap::new();
+    ///     map.add_range(65530, 65545);
+    ///     assert_eq!(map.range_cardinality(65534..65538), 4);
+    ///     assert_eq!(map.range_cardinality(..=65535), 6);
+    ///     assert_eq!(map.range_cardinality(65536..), 10);
+    ///     assert_eq!(map.range_cardinality(15..15), 0);
+    /// }
+    /// ```
+    pub fn range_cardinality<R: RangeBounds<usize>>(&self, range: R) -> usize {
+        match range_inclusive(&range) {
+            Some((start, end)) if start > 0 => self.rank(end) - self.rank(start - 1),
+            Some((_, end)) => self.rank(end),
+            None => 0,
+        }
+    }
+
+    /// 范围内元素的迭代器
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::RoaringBitMap;
+    /// fn main() {
+    ///     let mut map = RoaringBitMap::new();
+    ///     map.add_many(&vec![1, 9, 10, 100, 300000]);
+    ///     assert_eq!(map.iter_range(5..=100).collect::<Vec<_>>(), vec![9, 10, 100]);
+    ///     assert_eq!(map.iter_range(10..).rev().collect::<Vec<_>>(), vec![300000, 100, 10]);
+    /// }
+    /// ```
+    pub fn iter_range<R: RangeBounds<usize>>(&self, range: R) -> Iter<'_> {
+        let (min_val, max_val) = range_inclusive(&range).unwrap_or((1, 0));
+        Iter {
+            base: self,
+            len: self.range_cardinality(range),
+            min_val,
+            max_val: max_val.min(self.max_key),
+        }
+    }
+
+    /// 按[RoaringFormatSpec](https://github.com/RoaringBitmap/RoaringFormatSpec)序列化后的字节数
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::RoaringBitMap;
+    /// fn main() {
+    ///     let mut map = RoaringBitMap::new();
+    ///     map.add_many(&vec![1, 2, 65541]);
+    ///     assert_eq!(map.serialized_size(), 30);
+    /// }
+    /// ```
+    pub fn serialized_size(&self) -> usize {
+        let containers = self.sorted_containers();
+        Self::header_size(&containers)
+            + containers.iter().map(|(_, c)| c.serialized_size()).sum::<usize>()
+    }
+
+    fn has_run(containers: &[(usize, &TailContainer)]) -> bool {
+        containers.iter().any(|(_, c)| matches!(c, TailContainer::Run(_)))
+    }
+
+    /// 容器数据之前的字节数
+    fn header_size(containers: &[(usize, &TailContainer)]) -> usize {
+        let size = containers.len();
+        if !Self::has_run(containers) {
+            8 + size * 8
+        } else if size < NO_OFFSET_THRESHOLD {
+            4 + size.div_ceil(8) + size * 4
+        } else {
+            4 + size.div_ceil(8) + size * 8
+        }
+    }
+
+    /// 按[RoaringFormatSpec](https://github.com/RoaringBitmap/RoaringFormatSpec)序列化,
+    /// 可与Java/Go/C等官方实现互通, 该格式仅支持u32范围内的元素, 超出时返回`InvalidInput`
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::RoaringBitMap;
+    /// fn main() {
+    ///     let mut map = RoaringBitMap::new();
+    ///     map.add_many(&vec![1, 2, 65541]);
+    ///     map.add_range(200000, 210000);
+    ///     let mut buf = vec![];
+    ///     map.serialize_into(&mut buf).unwrap();
+    ///     assert_eq!(buf.len(), map.serialized_size());
+    ///     let other = RoaringBitMap::deserialize_from(&buf[..]).unwrap();
+    ///     assert_eq!(map, other);
+    /// }
+    /// ```
+    pub fn serialize_into<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let containers = self.sorted_containers();
+        if containers.last().is_some_and(|(h, _)| *h >= TAIL_NUM) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "value out of u32 range",
+            ));
+        }
+        let size = containers.len();
+        let has_run = Self::has_run(&containers);
+        if has_run {
+            let cookie = SERIAL_COOKIE | ((size as u32 - 1) << 16);
+            writer.write_all(&cookie.to_le_bytes())?;
+            let mut runs = vec![0u8; size.div_ceil(8)];
+            for (i, (_, container)) in containers.iter().enumerate() {
+                if matches!(container, TailContainer::Run(_)) {
+                    runs[i / 8] |= 1 << (i % 8);
+                }
+            }
+            writer.write_all(&runs)?;
+        } else {
+            writer.write_all(&SERIAL_COOKIE_NO_RUNCONTAINER.to_le_bytes())?;
+            writer.write_all(&(size as u32).to_le_bytes())?;
+        }
+        for (head, container) in &containers {
+            writer.write_all(&(*head as u16).to_le_bytes())?;
+            writer.write_all(&((container.len() - 1) as u16).to_le_bytes())?;
+        }
+        if !has_run || size >= NO_OFFSET_THRESHOLD {
+            let mut offset = Self::header_size(&containers);
+            for (_, container) in &containers {
+                writer.write_all(&(offset as u32).to_le_bytes())?;
+                offset += container.serialized_size();
+            }
+        }
+        for (_, container) in &containers {
+            container.serialize_into(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    /// 读取[RoaringFormatSpec](https://github.com/RoaringBitmap/RoaringFormatSpec)格式的位图,
+    /// 支持数组, 位图及Run三种容器
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::RoaringBitMap;
+    /// fn main() {
+    ///     // 含有一个Run容器[10, 109]的位图
+    ///     let buf = [0x3B, 0x30, 0, 0, 1, 0, 0, 99, 0, 1, 0, 10, 0, 99, 0];
+    ///     let map = RoaringBitMap::deserialize_from(&buf[..]).unwrap();
+    ///     assert_eq!(map.len(), 100);
+    ///     assert_eq!(map.min(), Some(10));
+    ///     assert_eq!(map.max(), Some(109));
+    /// }
+    /// ```
+    pub fn deserialize_from<R: Read>(mut reader: R) -> io::Result<Self> {
+        let cookie = read_u32(&mut reader)?;
+        let (size, runs) = if cookie == SERIAL_COOKIE_NO_RUNCONTAINER {
+            (read_u32(&mut reader)? as usize, vec![])
+        } else if cookie & 0xFFFF == SERIAL_COOKIE {
+            let size = (cookie >> 16) as usize + 1;
+            let mut runs = vec![0; size.div_ceil(8)];
+            reader.read_exact(&mut runs)?;
+            (size, runs)
+        } else {
+            return Err(invalid_data("unknown cookie"));
+        };
+        if size > TAIL_NUM {
+            return Err(invalid_data("too many containers"));
+        }
+        let mut headers = Vec::with_capacity(size);
+        for _ in 0..size {
+            let head = read_u16(&mut reader)? as usize;
+            let len = read_u16(&mut reader)? as usize + 1;
+            if headers.last().is_some_and(|(h, _)| *h >= head) {
+                return Err(invalid_data("unsorted container keys"));
+            }
+            headers.push((head, len));
+        }
+        if runs.is_empty() || size >= NO_OFFSET_THRESHOLD {
+            // 容器按顺序存放, 无需偏移量
+            io::copy(&mut (&mut reader).take(size as u64 * 4), &mut io::sink())?;
+        }
+        let mut map = RoaringBitMap::new();
+        for (i, (head, len)) in headers.into_iter().enumerate() {
+            let is_run = runs.get(i / 8).is_some_and(|v| v & (1 << (i % 8)) != 0);
+            let container = TailContainer::deserialize_from(&mut reader, len, is_run)?;
+            map.len += len;
+            map.max_key = head * TAIL_NUM + container.max().unwrap_or(0) as usize;
+            map.map.insert(head, container);
+        }
+        Ok(map)
+    }
+
     /// 是否保留，通过遍历进行循环，如果位图的容量非常大，可能效率相当低
     /// # Examples
     ///
@@ -500,6 +1216,22 @@ impl RoaringBitMap {
     }
 }
 
+fn read_u16<R: Read>(reader: &mut R) -> io::Result<u16> {
+    let mut buf = [0; 2];
+    reader.read_exact(&mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
 impl BitAnd for &RoaringBitMap {
     type Output=RoaringBitMap;
     fn bitand(self, rhs: Self) -> Self::Output {
@@ -662,8 +1394,9 @@ impl Debug for RoaringBitMap {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeSet;
 
-    use super::RoaringBitMap;
+    use super::{RoaringBitMap, TailContainer};
 
     #[test]
     fn test_display() {
@@ -679,4 +1412,150 @@ mod tests {
         let vec = m.iter().rev().collect::<Vec<_>>();
         assert_eq!(vec, vec![10240000111, 9, 3, 1]);
     }
+
+    #[test]
+    fn test_serialize() {
+        let mut m = RoaringBitMap::new();
+        m.add_many(&vec![1, 2, 65541]);
+        let mut buf = vec![];
+        m.serialize_into(&mut buf).unwrap();
+        #[rustfmt::skip]
+        assert_eq!(buf, vec![
+            0x3A, 0x30, 0, 0, 2, 0, 0, 0,
+            0, 0, 1, 0, 1, 0, 0, 0,
+            24, 0, 0, 0, 28, 0, 0, 0,
+            1, 0, 2, 0, 5, 0,
+        ]);
+
+        // 位图容器删除到4096个以内时按数组写入
+        m.add_range(131072, 131072 + 5000);
+        m.remove_range(131072, 131072 + 1000);
+        m.add(u32::MAX as usize);
+        let mut buf = vec![];
+        m.serialize_into(&mut buf).unwrap();
+        assert_eq!(buf.len(), m.serialized_size());
+        let other = RoaringBitMap::deserialize_from(&buf[..]).unwrap();
+        assert_eq!(other, m);
+        assert_eq!(other.max(), Some(u32::MAX as usize));
+        assert!(RoaringBitMap::deserialize_from(&buf[..buf.len() - 1]).is_err());
+
+        m.add(u32::MAX as usize + 1);
+        assert!(m.serialize_into(&mut vec![]).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_bitmap_container() {
+        let mut m = RoaringBitMap::new();
+        m.add_many(&[3, 70000]);
+        // 超过4096个元素的位图容器作为最后一个块
+        for v in (0..60000).step_by(2) {
+            m.add(131072 + v);
+        }
+        let mut buf = vec![];
+        m.serialize_into(&mut buf).unwrap();
+        let mut other = RoaringBitMap::deserialize_from(&buf[..]).unwrap();
+        assert_eq!(other.max(), Some(131072 + 59998));
+        assert_eq!(other.rank(131072 + 30000), 2 + 15001);
+        assert_eq!(other.iter().count(), m.len());
+        assert!(other.iter().eq(m.iter()));
+        other.optimize();
+        assert!(other.iter().eq(m.iter()));
+        assert_eq!(other.max(), Some(131072 + 59998));
+    }
+
+    #[test]
+    fn test_run_container() {
+        let mut m = RoaringBitMap::new();
+        m.add_range(0, 10_000_000);
+        assert!(m.map.values().all(|c| matches!(c, TailContainer::Run(_))));
+        assert_eq!(m.serialized_size(), 4 + 20 + 153 * 8 + 153 * 6);
+        m.remove_range(100, 10_000_000 - 100);
+        assert_eq!(m.len(), 200);
+        assert_eq!(m.map.len(), 2);
+
+        // 按固定种子随机操作, 与BTreeSet的结果比较
+        let mut seed = 0x2545F4914F6CDD1Du64;
+        let mut rand = |n: usize| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed as usize % n
+        };
+        let mut m = RoaringBitMap::new();
+        let mut set = BTreeSet::new();
+        for round in 0..2000 {
+            let start = rand(300000);
+            let end = start + rand(if round % 10 == 0 { 70000 } else { 50 });
+            match rand(4) {
+                0 => {
+                    m.add(start);
+                    set.insert(start);
+                }
+                1 => {
+                    assert_eq!(m.remove(start), set.remove(&start));
+                }
+                2 => {
+                    m.add_range(start, end);
+                    set.extend(start..=end);
+                }
+                _ => {
+                    m.remove_range(start, end);
+                    let removed: Vec<usize> = set.range(start..=end).copied().collect();
+                    removed.iter().for_each(|v| {
+                        set.remove(v);
+                    });
+                }
+            }
+            if round % 300 == 0 {
+                m.optimize();
+            }
+            assert_eq!(m.len(), set.len());
+        }
+        assert!(m.map.values().any(|c| matches!(c, TailContainer::Run(_))));
+        assert!(m.iter().eq(set.iter().copied()));
+        assert!(m.iter().rev().eq(set.iter().rev().copied()));
+        for v in (0..300000).step_by(997) {
+            assert_eq!(m.contains(&v), set.contains(&v));
+        }
+        let vals: Vec<usize> = set.iter().copied().collect();
+        for n in (0..vals.len()).step_by(101) {
+            assert_eq!(m.select(n), Some(vals[n]));
+            assert_eq!(m.rank(vals[n]), n + 1);
+        }
+
+        let mut buf = vec![];
+        m.serialize_into(&mut buf).unwrap();
+        assert_eq!(buf.len(), m.serialized_size());
+        let other = RoaringBitMap::deserialize_from(&buf[..]).unwrap();
+        assert!(other.iter().eq(set.iter().copied()));
+
+        let size = m.serialized_size();
+        m.shrink_to_fit();
+        assert!(m.serialized_size() <= size);
+        assert!(m.iter().eq(set.iter().copied()));
+    }
+
+    #[test]
+    fn test_rank_select() {
+        let mut m = RoaringBitMap::new();
+        // 跨越多个头部, 包含数组及位图两种容器
+        let mut vals: Vec<usize> = (0..300000).filter(|v| v % 7 == 3 || v % 11 == 0).collect();
+        vals.extend(200000..210000);
+        vals.sort();
+        vals.dedup();
+        m.add_many(&vals);
+        m.remove(vals.pop().unwrap());
+        assert_eq!(m.min(), Some(0));
+        assert_eq!(m.max(), vals.last().copied());
+        for n in (0..vals.len()).step_by(97) {
+            assert_eq!(m.select(n), Some(vals[n]));
+            assert_eq!(m.rank(vals[n]), n + 1);
+        }
+        for (a, b) in [(0, 70000), (65530, 65545), (17, 5), (199999, 200001), (250000, usize::MAX)] {
+            let expect: Vec<usize> = vals.iter().copied().filter(|v| *v >= a && *v <= b).collect();
+            assert_eq!(m.range_cardinality(a..=b), expect.len());
+            assert_eq!(m.iter_range(a..=b).collect::<Vec<_>>(), expect);
+            assert_eq!(m.iter_range(a..=b).rev().count(), expect.len());
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use super::{as_millis, Limiter, RetryAfter};
+
+/// 固定窗口计数, 时间按`window`对齐切分成窗口, 每个窗口内最多消耗`limit`,
+/// 实现最简单, 但在窗口交界处最多可能通过两倍的流量
+///
+/// # Examples
+///
+/// ```
+/// use algorithm::{FixedWindow, Limiter};
+/// use std::time::Duration;
+/// fn main() {
+///     let mut window = FixedWindow::new(2, Duration::from_secs(1));
+///     assert!(window.check_at(100, 1).is_ok());
+///     assert!(window.check_at(200, 1).is_ok());
+///     // 需要等到下一个窗口
+///     assert_eq!(window.check_at(400, 1).unwrap_err().duration(), Duration::from_millis(600));
+///     assert!(window.check_at(1000, 2).is_ok());
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FixedWindow {
+    limit: u64,
+    window: u64,
+    /// 当前窗口的起始时间
+    start: u64,
+    count: u64,
+}
+
+impl FixedWindow {
+    pub fn new(limit: u64, window: Duration) -> Self {
+        Self {
+            limit,
+            window: as_millis(window),
+            start: 0,
+            count: 0,
+        }
+    }
+
+    /// 当前窗口内已消耗的数量
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+impl Limiter for FixedWindow {
+    fn check_at(&mut self, now: u64, cost: u64) -> Result<(), RetryAfter> {
+        if cost > self.limit {
+            return Err(RetryAfter::never());
+        }
+        let start = now - now % self.window;
+        if start > self.start {
+            self.start = start;
+            self.count = 0;
+        }
+        if self.count + cost <= self.limit {
+            self.count += cost;
+            Ok(())
+        } else {
+            Err(RetryAfter::from_millis(
+                (self.start + self.window).saturating_sub(now),
+            ))
+        }
+    }
+
+    fn idle_timeout(&self) -> u64 {
+        self.window
+    }
+}
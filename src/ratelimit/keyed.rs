@@ -0,0 +1,101 @@
+use std::hash::Hash;
+
+use crate::{get_milltimestamp, DefaultHasher, LruCache};
+
+use super::{
+    FixedWindow, LeakyBucket, Limiter, RetryAfter, SlidingWindowCounter, SlidingWindowLog,
+    TokenBucket,
+};
+
+pub type KeyedTokenBucket<K> = KeyedLimiter<K, TokenBucket>;
+pub type KeyedLeakyBucket<K> = KeyedLimiter<K, LeakyBucket>;
+pub type KeyedFixedWindow<K> = KeyedLimiter<K, FixedWindow>;
+pub type KeyedSlidingWindowLog<K> = KeyedLimiter<K, SlidingWindowLog>;
+pub type KeyedSlidingWindowCounter<K> = KeyedLimiter<K, SlidingWindowCounter>;
+
+/// 按key限流, 每个key拥有一份独立的限流器, 由[`LruCache`]保存,
+/// 超出容量时淘汰最久未访问的key, 开启`ttl`时空闲超过[`Limiter::idle_timeout`]的key会过期
+///
+/// 因ttl过期的key状态已与新建时相同, 过期不会放宽限流;
+/// 但因超出容量被淘汰的key会重置为新建时的状态, 被限流的key可能因此立即重新通过,
+/// `capacity`应不小于一个限流周期内的活跃key数量
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "sim")]
+/// use algorithm::{KeyedTokenBucket, SimClock, TokenBucket};
+/// use std::time::Duration;
+/// # #[cfg(feature = "sim")]
+/// fn main() {
+///     let mut clock = SimClock::new(0);
+///     let mut limiter = KeyedTokenBucket::new(TokenBucket::new(2, 1, Duration::from_secs(1)), 100);
+///     assert!(limiter.check("alice", 2).is_ok());
+///     assert_eq!(limiter.check("alice", 1).unwrap_err().duration(), Duration::from_secs(1));
+///     assert!(limiter.check("bob", 1).is_ok());
+///     clock.advance(1000);
+///     assert!(limiter.check("alice", 1).is_ok());
+///     assert_eq!(limiter.len(), 2);
+/// }
+/// # #[cfg(not(feature = "sim"))]
+/// # fn main() {}
+/// ```
+pub struct KeyedLimiter<K, L> {
+    template: L,
+    cache: LruCache<K, L, DefaultHasher>,
+}
+
+impl<K: Hash + Eq, L: Limiter + Clone> KeyedLimiter<K, L> {
+    /// 以`limiter`为每个新key的初始状态, 最多保存`capacity`个key
+    pub fn new(limiter: L, capacity: usize) -> Self {
+        Self {
+            template: limiter,
+            cache: LruCache::new(capacity),
+        }
+    }
+
+    /// 以[`get_milltimestamp`]为当前时间对`key`消耗`cost`
+    pub fn check(&mut self, key: K, cost: u64) -> Result<(), RetryAfter> {
+        self.check_at(key, get_milltimestamp(), cost)
+    }
+
+    /// 在`now`(毫秒时间戳)时对`key`消耗`cost`, key的过期时间仍以[`get_milltimestamp`]计算
+    pub fn check_at(&mut self, key: K, now: u64, cost: u64) -> Result<(), RetryAfter> {
+        #[cfg(feature = "ttl")]
+        let ttl = self.template.idle_timeout().div_ceil(1000);
+        if let Some(limiter) = self.cache.get_mut(&key) {
+            let ret = limiter.check_at(now, cost);
+            #[cfg(feature = "ttl")]
+            if ret.is_ok() {
+                self.cache.set_ttl(&key, ttl.max(1));
+            }
+            return ret;
+        }
+        let mut limiter = self.template.clone();
+        // 新建的限流器检查失败时状态未变化, 无需保存
+        limiter.check_at(now, cost)?;
+        #[cfg(feature = "ttl")]
+        self.cache.insert_with_ttl(key, limiter, ttl.max(1));
+        #[cfg(not(feature = "ttl"))]
+        self.cache.insert(key, limiter);
+        Ok(())
+    }
+
+    /// 当前保存的key数量, 可能包含已过期但尚未清理的key
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// 重置`key`的限流状态
+    pub fn remove(&mut self, key: &K) -> Option<L> {
+        self.cache.remove(key).map(|(_, v)| v)
+    }
+
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+}
@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use super::{as_millis, retry_after, Limiter, RetryAfter};
+
+/// 漏桶, 每次请求向桶中加入`cost`的水量, 桶以每`per`漏出`leak`的速度匀速流出,
+/// 水量超过`capacity`时拒绝, 与令牌桶相反, 从空桶开始计量, 适合平滑流出速率的场景
+///
+/// # Examples
+///
+/// ```
+/// use algorithm::{LeakyBucket, Limiter};
+/// use std::time::Duration;
+/// fn main() {
+///     // 桶容量为3, 每100毫秒漏出1
+///     let mut bucket = LeakyBucket::new(3, 1, Duration::from_millis(100));
+///     for _ in 0..3 {
+///         assert!(bucket.check_at(0, 1).is_ok());
+///     }
+///     assert_eq!(bucket.check_at(50, 1).unwrap_err().duration(), Duration::from_millis(50));
+///     assert!(bucket.check_at(100, 1).is_ok());
+///     assert_eq!(bucket.level(), 3);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LeakyBucket {
+    capacity: u64,
+    /// 每毫秒漏出的水量
+    rate: f64,
+    level: f64,
+    last: Option<u64>,
+}
+
+impl LeakyBucket {
+    pub fn new(capacity: u64, leak: u64, per: Duration) -> Self {
+        debug_assert!(leak > 0, "漏出的速度必须大于0");
+        Self {
+            capacity,
+            rate: leak.max(1) as f64 / as_millis(per) as f64,
+            level: 0.0,
+            last: None,
+        }
+    }
+
+    /// 当前桶中的水量, 未计算上次检查之后漏出的水量
+    pub fn level(&self) -> u64 {
+        self.level.ceil() as u64
+    }
+
+    fn leak(&mut self, now: u64) {
+        let last = self.last.unwrap_or(now);
+        if now > last {
+            self.level = (self.level - (now - last) as f64 * self.rate).max(0.0);
+        }
+        self.last = Some(now.max(last));
+    }
+}
+
+impl Limiter for LeakyBucket {
+    fn check_at(&mut self, now: u64, cost: u64) -> Result<(), RetryAfter> {
+        if cost > self.capacity {
+            return Err(RetryAfter::never());
+        }
+        self.leak(now);
+        let over = self.level + cost as f64 - self.capacity as f64;
+        if over <= 0.0 {
+            self.level += cost as f64;
+            Ok(())
+        } else {
+            Err(retry_after(over / self.rate))
+        }
+    }
+
+    fn idle_timeout(&self) -> u64 {
+        (self.capacity as f64 / self.rate).ceil() as u64
+    }
+}
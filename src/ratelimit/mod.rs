@@ -0,0 +1,131 @@
+mod fixed_window;
+mod keyed;
+mod leaky_bucket;
+mod sliding_window;
+mod token_bucket;
+
+use std::{error::Error, fmt, time::Duration};
+
+use crate::get_milltimestamp;
+
+pub use fixed_window::FixedWindow;
+pub use keyed::{
+    KeyedFixedWindow, KeyedLeakyBucket, KeyedLimiter, KeyedSlidingWindowCounter,
+    KeyedSlidingWindowLog, KeyedTokenBucket,
+};
+pub use leaky_bucket::LeakyBucket;
+pub use sliding_window::{SlidingWindowCounter, SlidingWindowLog};
+pub use token_bucket::TokenBucket;
+
+/// 超出限制时需要等待的时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RetryAfter(Duration);
+
+impl RetryAfter {
+    pub fn from_millis(millis: u64) -> Self {
+        Self(Duration::from_millis(millis.max(1)))
+    }
+
+    /// 单次消耗超出了限流器的容量, 等待多久都不会成功
+    pub fn never() -> Self {
+        Self(Duration::MAX)
+    }
+
+    pub fn is_never(&self) -> bool {
+        self.0 == Duration::MAX
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.0
+    }
+}
+
+impl fmt::Display for RetryAfter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_never() {
+            f.write_str("rate limited, cost exceeds capacity")
+        } else {
+            write!(f, "rate limited, retry after {:?}", self.0)
+        }
+    }
+}
+
+impl Error for RetryAfter {}
+
+/// 单个key的限流器, 时间均以毫秒为单位
+pub trait Limiter {
+    /// 在`now`(毫秒时间戳)时消耗`cost`, 超出限制时不消耗任何额度并返回需要等待的时间
+    fn check_at(&mut self, now: u64, cost: u64) -> Result<(), RetryAfter>;
+
+    /// 以[`get_milltimestamp`]为当前时间消耗`cost`, 开启`sim` feature后可由`SimClock`控制
+    fn check(&mut self, cost: u64) -> Result<(), RetryAfter> {
+        self.check_at(get_milltimestamp(), cost)
+    }
+
+    /// 空闲超过该时间(毫秒)后状态与新建时相同, [`KeyedLimiter`]据此淘汰空闲的key
+    fn idle_timeout(&self) -> u64;
+}
+
+/// 将毫秒数转换成等待时间, 不足1毫秒的按1毫秒计算
+fn retry_after(millis: f64) -> RetryAfter {
+    RetryAfter::from_millis(millis.ceil() as u64)
+}
+
+fn as_millis(duration: Duration) -> u64 {
+    debug_assert!(duration.as_millis() > 0, "时间间隔不能小于1毫秒");
+    (duration.as_millis() as u64).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_retry_after() {
+        let second = Duration::from_secs(1);
+        let mut limiters: Vec<Box<dyn Limiter>> = vec![
+            Box::new(TokenBucket::new(4, 4, second)),
+            Box::new(LeakyBucket::new(4, 4, second)),
+            Box::new(FixedWindow::new(4, second)),
+            Box::new(SlidingWindowLog::new(4, second)),
+            Box::new(SlidingWindowCounter::new(4, second)),
+        ];
+        for limiter in limiters.iter_mut() {
+            let mut now = 123;
+            for _ in 0..50 {
+                match limiter.check_at(now, 3) {
+                    Ok(()) => now += 7,
+                    Err(retry) => {
+                        let wait = retry.duration().as_millis() as u64;
+                        assert!(!retry.is_never() && wait > 0);
+                        // 等待到返回的时间后必定可以通过
+                        assert!(limiter.check_at(now + wait, 3).is_ok());
+                        now += wait;
+                    }
+                }
+            }
+            assert!(limiter.check_at(now, 5).unwrap_err().is_never());
+        }
+    }
+
+    #[cfg(feature = "sim")]
+    #[test]
+    fn test_keyed_evict() {
+        let mut clock = crate::SimClock::new(0);
+        let mut limiter = KeyedFixedWindow::new(FixedWindow::new(1, Duration::from_secs(1)), 2);
+        assert!(limiter.check(1, 1).is_ok());
+        assert!(limiter.check(2, 1).is_ok());
+        assert!(limiter.check(1, 1).is_err());
+        // 超出容量时淘汰最久未访问的key, 被淘汰的key 2重置后在同一窗口内再次通过
+        assert!(limiter.check(3, 1).is_ok());
+        assert_eq!(limiter.len(), 2);
+        assert!(limiter.check(2, 1).is_ok());
+        assert!(limiter.check(3, 1).is_err());
+        assert!(limiter.remove(&3).is_some());
+        assert!(limiter.check(3, 1).is_ok());
+        clock.advance(1000);
+        assert!(limiter.check(3, 1).is_ok());
+    }
+}
@@ -0,0 +1,174 @@
+use std::{collections::VecDeque, time::Duration};
+
+use super::{as_millis, retry_after, Limiter, RetryAfter};
+
+/// 滑动窗口日志, 记录每次通过的时间, 任意`window`长度的时间内最多消耗`limit`,
+/// 结果精确但内存与窗口内的请求数成正比
+///
+/// # Examples
+///
+/// ```
+/// use algorithm::{Limiter, SlidingWindowLog};
+/// use std::time::Duration;
+/// fn main() {
+///     let mut log = SlidingWindowLog::new(3, Duration::from_secs(1));
+///     assert!(log.check_at(0, 2).is_ok());
+///     assert!(log.check_at(600, 1).is_ok());
+///     // 需要等到0时刻的记录滑出窗口
+///     assert_eq!(log.check_at(900, 1).unwrap_err().duration(), Duration::from_millis(100));
+///     assert!(log.check_at(1000, 2).is_ok());
+///     assert_eq!(log.len(), 2);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SlidingWindowLog {
+    limit: u64,
+    window: u64,
+    /// 通过的时间及消耗的数量, 按时间排序
+    log: VecDeque<(u64, u64)>,
+    total: u64,
+}
+
+impl SlidingWindowLog {
+    pub fn new(limit: u64, window: Duration) -> Self {
+        Self {
+            limit,
+            window: as_millis(window),
+            log: VecDeque::new(),
+            total: 0,
+        }
+    }
+
+    /// 窗口内的记录数
+    pub fn len(&self) -> usize {
+        self.log.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.log.is_empty()
+    }
+
+    fn expire(&mut self, now: u64) {
+        while let Some(&(at, cost)) = self.log.front() {
+            if at + self.window > now {
+                break;
+            }
+            self.total -= cost;
+            self.log.pop_front();
+        }
+    }
+}
+
+impl Limiter for SlidingWindowLog {
+    fn check_at(&mut self, now: u64, cost: u64) -> Result<(), RetryAfter> {
+        if cost > self.limit {
+            return Err(RetryAfter::never());
+        }
+        self.expire(now);
+        if self.total + cost <= self.limit {
+            // 时间回退时保持记录有序
+            let at = self.log.back().map_or(now, |&(last, _)| last.max(now));
+            self.log.push_back((at, cost));
+            self.total += cost;
+            return Ok(());
+        }
+        // 找到滑出窗口后足够容纳本次消耗的那条记录
+        let mut total = self.total;
+        for &(at, c) in self.log.iter() {
+            total -= c;
+            if total + cost <= self.limit {
+                return Err(RetryAfter::from_millis((at + self.window).saturating_sub(now)));
+            }
+        }
+        unreachable!("cost不超过limit时清空窗口必定可以通过")
+    }
+
+    fn idle_timeout(&self) -> u64 {
+        self.window
+    }
+}
+
+/// 滑动窗口计数, 以上一个固定窗口的计数按剩余比例加上当前窗口的计数估算滑动窗口内的总量,
+/// 内存固定, 结果为近似值
+///
+/// # Examples
+///
+/// ```
+/// use algorithm::{Limiter, SlidingWindowCounter};
+/// use std::time::Duration;
+/// fn main() {
+///     let mut counter = SlidingWindowCounter::new(10, Duration::from_secs(1));
+///     assert!(counter.check_at(500, 10).is_ok());
+///     // 1250时上个窗口按75%计算, 估算为7.5
+///     assert!(counter.check_at(1250, 2).is_ok());
+///     let retry = counter.check_at(1250, 2).unwrap_err();
+///     assert_eq!(retry.duration(), Duration::from_millis(150));
+///     assert!(counter.check_at(1400, 2).is_ok());
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SlidingWindowCounter {
+    limit: u64,
+    window: u64,
+    /// 当前窗口的起始时间
+    start: u64,
+    cur: u64,
+    prev: u64,
+}
+
+impl SlidingWindowCounter {
+    pub fn new(limit: u64, window: Duration) -> Self {
+        Self {
+            limit,
+            window: as_millis(window),
+            start: 0,
+            cur: 0,
+            prev: 0,
+        }
+    }
+
+    fn roll(&mut self, now: u64) {
+        let start = now - now % self.window;
+        if start <= self.start {
+            return;
+        }
+        self.prev = if start - self.start == self.window {
+            self.cur
+        } else {
+            0
+        };
+        self.cur = 0;
+        self.start = start;
+    }
+}
+
+impl Limiter for SlidingWindowCounter {
+    fn check_at(&mut self, now: u64, cost: u64) -> Result<(), RetryAfter> {
+        if cost > self.limit {
+            return Err(RetryAfter::never());
+        }
+        self.roll(now);
+        let window = self.window as f64;
+        // 当前窗口中已经过的时间
+        let elapsed = now.saturating_sub(self.start) as f64;
+        let weight = self.prev as f64 * (window - elapsed) / window;
+        let room = self.limit as f64 - self.cur as f64 - cost as f64;
+        if weight <= room {
+            self.cur += cost;
+            return Ok(());
+        }
+        // 等待上个窗口的计数`prev`按比例衰减到不超过`room`, 返回从窗口开始需要经过的时间
+        let decay = |prev: u64, room: f64| (window - room * window / prev as f64).max(0.0);
+        if room >= 0.0 {
+            Err(retry_after(decay(self.prev, room) - elapsed))
+        } else {
+            // 当前窗口已经不够, 需等到下个窗口, 当前窗口的计数成为上个窗口
+            let room = (self.limit - cost) as f64;
+            Err(retry_after(window - elapsed + decay(self.cur, room)))
+        }
+    }
+
+    fn idle_timeout(&self) -> u64 {
+        self.window * 2
+    }
+}
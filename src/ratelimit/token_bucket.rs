@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use super::{as_millis, retry_after, Limiter, RetryAfter};
+
+/// 令牌桶, 桶中最多存放`capacity`个令牌, 每`per`的时间补充`refill`个令牌,
+/// 允许一次性消耗掉积攒的令牌, 适合允许突发流量的场景
+///
+/// # Examples
+///
+/// ```
+/// use algorithm::{Limiter, TokenBucket};
+/// use std::time::Duration;
+/// fn main() {
+///     // 最多突发10个, 每秒补充5个
+///     let mut bucket = TokenBucket::new(10, 5, Duration::from_secs(1));
+///     assert!(bucket.check_at(0, 10).is_ok());
+///     let retry = bucket.check_at(0, 1).unwrap_err();
+///     assert_eq!(retry.duration(), Duration::from_millis(200));
+///     assert!(bucket.check_at(200, 1).is_ok());
+///     assert!(bucket.check_at(200, 11).unwrap_err().is_never());
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: u64,
+    /// 每毫秒补充的令牌数
+    rate: f64,
+    tokens: f64,
+    last: Option<u64>,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: u64, refill: u64, per: Duration) -> Self {
+        debug_assert!(refill > 0, "补充的令牌数必须大于0");
+        Self {
+            capacity,
+            rate: refill.max(1) as f64 / as_millis(per) as f64,
+            tokens: capacity as f64,
+            last: None,
+        }
+    }
+
+    /// 当前桶中的令牌数, 未计算上次检查之后补充的令牌
+    pub fn tokens(&self) -> u64 {
+        self.tokens as u64
+    }
+
+    fn refill(&mut self, now: u64) {
+        let last = self.last.unwrap_or(now);
+        if now > last {
+            let tokens = self.tokens + (now - last) as f64 * self.rate;
+            self.tokens = tokens.min(self.capacity as f64);
+        }
+        self.last = Some(now.max(last));
+    }
+}
+
+impl Limiter for TokenBucket {
+    fn check_at(&mut self, now: u64, cost: u64) -> Result<(), RetryAfter> {
+        if cost > self.capacity {
+            return Err(RetryAfter::never());
+        }
+        self.refill(now);
+        let cost = cost as f64;
+        if self.tokens >= cost {
+            self.tokens -= cost;
+            Ok(())
+        } else {
+            Err(retry_after((cost - self.tokens) / self.rate))
+        }
+    }
+
+    fn idle_timeout(&self) -> u64 {
+        (self.capacity as f64 / self.rate).ceil() as u64
+    }
+}
@@ -0,0 +1,400 @@
+use std::{
+    error::Error,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
+};
+
+use super::{Timer, TimerWheel};
+
+/// 异步定时器的时间来源, 返回自某个固定起点以来经过的时间
+pub trait TickSource: Send + Sync + 'static {
+    fn elapsed(&self) -> Duration;
+}
+
+/// 以系统单调时钟[`Instant`]为时间来源
+pub struct InstantSource(Instant);
+
+impl InstantSource {
+    pub fn new() -> Self {
+        Self(Instant::now())
+    }
+}
+
+impl Default for InstantSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TickSource for InstantSource {
+    fn elapsed(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
+
+struct WakeEntry {
+    when: u64,
+    waker: Option<Waker>,
+}
+
+impl Timer for WakeEntry {
+    fn when(&self) -> u64 {
+        self.when
+    }
+}
+
+struct Inner {
+    wheel: TimerWheel<WakeEntry>,
+    /// 时轮已经递进的刻度数
+    passed: u64,
+}
+
+struct Shared {
+    source: Box<dyn TickSource>,
+    unit: Duration,
+    inner: Mutex<Inner>,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn ticks(&self, duration: Duration) -> u64 {
+        (duration.as_nanos() / self.unit.as_nanos()) as u64
+    }
+}
+
+/// 基于[`TimerWheel`]的异步定时器, 不依赖任何异步运行时
+///
+/// 时轮中存放的是等待中的`Waker`, 由使用者以任意方式周期性的调用[`AsyncTimer::tick`]推进时轮,
+/// 到期的`Waker`将被唤醒, 时间的来源可由[`TickSource`]替换
+///
+/// # Examples
+///
+/// ```
+/// use algorithm::{AsyncTimer, TickSource};
+/// use std::future::Future;
+/// use std::pin::pin;
+/// use std::sync::{atomic::{AtomicU64, Ordering}, Arc};
+/// use std::task::{Context, Poll, Waker};
+/// use std::time::Duration;
+///
+/// #[derive(Clone, Default)]
+/// struct ManualSource(Arc<AtomicU64>);
+/// impl TickSource for ManualSource {
+///     fn elapsed(&self) -> Duration {
+///         Duration::from_millis(self.0.load(Ordering::Relaxed))
+///     }
+/// }
+///
+/// fn main() {
+///     let source = ManualSource::default();
+///     let timer = AsyncTimer::new(source.clone(), Duration::from_millis(1));
+///     let mut cx = Context::from_waker(Waker::noop());
+///     let mut sleep = pin!(timer.sleep(Duration::from_millis(10)));
+///     assert!(sleep.as_mut().poll(&mut cx).is_pending());
+///     source.0.store(5, Ordering::Relaxed);
+///     assert_eq!(timer.tick(), 0);
+///     source.0.store(10, Ordering::Relaxed);
+///     assert_eq!(timer.tick(), 1);
+///     assert!(sleep.as_mut().poll(&mut cx).is_ready());
+/// }
+/// ```
+#[derive(Clone)]
+pub struct AsyncTimer {
+    shared: Arc<Shared>,
+}
+
+impl AsyncTimer {
+    /// 创建异步定时器, `unit`为时轮每个刻度的时间
+    pub fn new<S: TickSource>(source: S, unit: Duration) -> Self {
+        debug_assert!(!unit.is_zero());
+        let mut wheel = TimerWheel::new();
+        wheel.append_timer_wheel(1024, "TickWheel");
+        wheel.append_timer_wheel(1024, "RoundWheel");
+        let passed = (source.elapsed().as_nanos() / unit.as_nanos()) as u64;
+        Self {
+            shared: Arc::new(Shared {
+                source: Box::new(source),
+                unit,
+                inner: Mutex::new(Inner { wheel, passed }),
+            }),
+        }
+    }
+
+    /// 以系统单调时钟创建异步定时器
+    pub fn with_unit(unit: Duration) -> Self {
+        Self::new(InstantSource::new(), unit)
+    }
+
+    /// 推进时轮到时间来源的当前时间, 唤醒所有到期的定时器, 返回唤醒的数量
+    pub fn tick(&self) -> usize {
+        let now = self.shared.ticks(self.shared.source.elapsed());
+        let expired = {
+            let mut inner = self.shared.lock();
+            if now <= inner.passed {
+                return 0;
+            }
+            let delta = now - inner.passed;
+            inner.passed = now;
+            inner.wheel.update_deltatime(delta).unwrap_or_default()
+        };
+        let len = expired.len();
+        // 在锁外唤醒, 避免唤醒时重入
+        for (_, entry) in expired {
+            if let Some(waker) = entry.waker {
+                waker.wake();
+            }
+        }
+        len
+    }
+
+    /// 距离下一个定时器到期的时间, 可用于决定驱动线程的休眠时长
+    pub fn next_delay(&self) -> Duration {
+        match self.shared.lock().wheel.next_delay() {
+            Some(delay) => self
+                .shared
+                .unit
+                .saturating_mul(delay.clamp(1, u32::MAX as u64) as u32),
+            None => Duration::MAX,
+        }
+    }
+
+    /// 当前等待中的定时器数量
+    pub fn len(&self) -> usize {
+        self.shared.lock().wheel.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 等待指定的时间
+    pub fn sleep(&self, duration: Duration) -> Sleep {
+        let now = self.shared.ticks(self.shared.source.elapsed());
+        self.sleep_until_tick(now + self.shared.ticks(duration))
+    }
+
+    /// 等待到时间来源的第`deadline`个刻度, 已经过去时在下一次推进时唤醒
+    fn sleep_until_tick(&self, deadline: u64) -> Sleep {
+        let mut inner = self.shared.lock();
+        // 时轮可能还未推进到当前时间, 以时轮已经递进的刻度计算延时
+        let when = deadline.saturating_sub(inner.passed);
+        let id = inner.wheel.add_timer(WakeEntry { when, waker: None });
+        drop(inner);
+        Sleep {
+            shared: self.shared.clone(),
+            id: Some(id),
+        }
+    }
+
+    /// 为future设置超时时间, 超时后返回[`Elapsed`]
+    ///
+    /// ```
+    /// use algorithm::AsyncTimer;
+    /// use std::future::{pending, Future};
+    /// use std::pin::pin;
+    /// use std::task::{Context, Poll, Waker};
+    /// use std::time::Duration;
+    /// fn main() {
+    ///     let timer = AsyncTimer::with_unit(Duration::from_millis(1));
+    ///     let mut cx = Context::from_waker(Waker::noop());
+    ///     let mut ready = pin!(timer.timeout(async { 1 }, Duration::from_millis(10)));
+    ///     assert_eq!(ready.as_mut().poll(&mut cx), Poll::Ready(Ok(1)));
+    ///     let mut fut = pin!(timer.timeout(pending::<()>(), Duration::from_millis(1)));
+    ///     assert!(fut.as_mut().poll(&mut cx).is_pending());
+    ///     std::thread::sleep(Duration::from_millis(5));
+    ///     timer.tick();
+    ///     assert!(matches!(fut.as_mut().poll(&mut cx), Poll::Ready(Err(_))));
+    /// }
+    /// ```
+    pub fn timeout<F: Future>(&self, fut: F, duration: Duration) -> Timeout<F> {
+        Timeout {
+            fut,
+            sleep: self.sleep(duration),
+        }
+    }
+
+    /// 创建每隔`period`触发一次的定时器, 第一次在`period`后触发
+    pub fn interval(&self, period: Duration) -> Interval {
+        let step = self.shared.ticks(period).max(1);
+        let deadline = self.shared.ticks(self.shared.source.elapsed()) + step;
+        Interval {
+            timer: self.clone(),
+            period,
+            step,
+            deadline,
+            sleep: self.sleep_until_tick(deadline),
+        }
+    }
+}
+
+/// [`AsyncTimer::sleep`]返回的future, 析构时自动取消定时器
+pub struct Sleep {
+    shared: Arc<Shared>,
+    id: Option<u64>,
+}
+
+impl Sleep {
+    /// 是否已经到期
+    pub fn is_elapsed(&self) -> bool {
+        match self.id {
+            Some(id) => self.shared.lock().wheel.get_timer(&id).is_none(),
+            None => true,
+        }
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let id = match self.id {
+            Some(id) => id,
+            None => return Poll::Ready(()),
+        };
+        let pending = {
+            let mut inner = self.shared.lock();
+            match inner.wheel.get_mut_timer(&id) {
+                Some(entry) => {
+                    match &entry.waker {
+                        Some(waker) if waker.will_wake(cx.waker()) => {}
+                        _ => entry.waker = Some(cx.waker().clone()),
+                    }
+                    true
+                }
+                None => false,
+            }
+        };
+        if pending {
+            Poll::Pending
+        } else {
+            self.id = None;
+            Poll::Ready(())
+        }
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            self.shared.lock().wheel.del_timer(id);
+        }
+    }
+}
+
+/// 超时的错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed;
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("deadline has elapsed")
+    }
+}
+
+impl Error for Elapsed {}
+
+/// [`AsyncTimer::timeout`]返回的future
+pub struct Timeout<F> {
+    fut: F,
+    sleep: Sleep,
+}
+
+impl<F> Timeout<F> {
+    pub fn into_inner(self) -> F {
+        self.fut
+    }
+}
+
+impl<F: Future> Future for Timeout<F> {
+    type Output = Result<F::Output, Elapsed>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // 只有fut需要保持固定, sleep可以任意移动
+        let this = unsafe { self.get_unchecked_mut() };
+        let fut = unsafe { Pin::new_unchecked(&mut this.fut) };
+        if let Poll::Ready(v) = fut.poll(cx) {
+            return Poll::Ready(Ok(v));
+        }
+        match Pin::new(&mut this.sleep).poll(cx) {
+            Poll::Ready(()) => Poll::Ready(Err(Elapsed)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// [`AsyncTimer::interval`]返回的周期定时器
+///
+/// 每个周期的触发时间为上一个周期的触发时间加上`period`, 不会因轮询的延迟而累积漂移,
+/// 错过的周期将在之后的推进中依次触发
+///
+/// ```
+/// use algorithm::{AsyncTimer, TickSource};
+/// use std::sync::{atomic::{AtomicU64, Ordering}, Arc};
+/// use std::task::{Context, Waker};
+/// use std::time::Duration;
+///
+/// #[derive(Clone, Default)]
+/// struct ManualSource(Arc<AtomicU64>);
+/// impl TickSource for ManualSource {
+///     fn elapsed(&self) -> Duration {
+///         Duration::from_millis(self.0.load(Ordering::Relaxed))
+///     }
+/// }
+///
+/// fn main() {
+///     let source = ManualSource::default();
+///     let timer = AsyncTimer::new(source.clone(), Duration::from_millis(1));
+///     let mut interval = timer.interval(Duration::from_millis(10));
+///     let mut cx = Context::from_waker(Waker::noop());
+///     assert!(interval.poll_tick(&mut cx).is_pending());
+///     // 延迟3毫秒才轮询, 下一个周期仍在20毫秒时触发
+///     source.0.store(13, Ordering::Relaxed);
+///     timer.tick();
+///     assert!(interval.poll_tick(&mut cx).is_ready());
+///     source.0.store(19, Ordering::Relaxed);
+///     timer.tick();
+///     assert!(interval.poll_tick(&mut cx).is_pending());
+///     source.0.store(20, Ordering::Relaxed);
+///     timer.tick();
+///     assert!(interval.poll_tick(&mut cx).is_ready());
+/// }
+/// ```
+pub struct Interval {
+    timer: AsyncTimer,
+    period: Duration,
+    /// 每个周期的刻度数
+    step: u64,
+    /// 当前周期的触发刻度
+    deadline: u64,
+    sleep: Sleep,
+}
+
+impl Interval {
+    /// 轮询是否到达下一个周期, 到达后以本周期的触发时间为起点开始下一个周期
+    pub fn poll_tick(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        match Pin::new(&mut self.sleep).poll(cx) {
+            Poll::Ready(()) => {
+                self.deadline += self.step;
+                self.sleep = self.timer.sleep_until_tick(self.deadline);
+                Poll::Ready(())
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    /// 等待下一个周期
+    pub async fn tick(&mut self) {
+        std::future::poll_fn(|cx| self.poll_tick(cx)).await
+    }
+
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+}
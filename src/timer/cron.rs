@@ -0,0 +1,377 @@
+use std::{error::Error, fmt, str::FromStr};
+
+use crate::get_milltimestamp;
+
+use super::Timer;
+
+const MONTH_NAMES: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const WEEK_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// 向后查找时最多查找的年数, 超出则认为该表达式不会再触发, 如2月30日
+const MAX_YEARS: i64 = 400;
+
+/// 解析cron表达式的错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CronError {
+    /// 字段数不是5或6个
+    FieldCount(usize),
+    /// 无法解析或超出范围的字段
+    InvalidField(String),
+}
+
+impl fmt::Display for CronError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CronError::FieldCount(n) => write!(f, "cron expression needs 5 or 6 fields, got {}", n),
+            CronError::InvalidField(field) => write!(f, "invalid cron field `{}`", field),
+        }
+    }
+}
+
+impl Error for CronError {}
+
+/// 字段允许的取值, 以位图表示
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Field(u64);
+
+impl Field {
+    fn has(&self, v: u32) -> bool {
+        self.0 & (1 << v) != 0
+    }
+
+    /// 不小于`from`的第一个允许的取值
+    fn next(&self, from: u32) -> Option<u32> {
+        if from >= 64 {
+            return None;
+        }
+        let bits = self.0 >> from;
+        (bits != 0).then(|| from + bits.trailing_zeros())
+    }
+
+    /// 解析单个字段, 支持`*`, `a`, `a-b`, `*/n`, `a-b/n`, `a/n`及以`,`分隔的列表
+    fn parse(
+        field: &str,
+        min: u32,
+        max: u32,
+        names: &[&str],
+        offset: u32,
+    ) -> Result<Self, CronError> {
+        let invalid = || CronError::InvalidField(field.to_string());
+        let value = |s: &str| -> Result<u32, CronError> {
+            if let Some(pos) = names.iter().position(|n| n.eq_ignore_ascii_case(s)) {
+                return Ok(pos as u32 + offset);
+            }
+            s.parse::<u32>().map_err(|_| invalid())
+        };
+        let mut bits = 0u64;
+        for part in field.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => (range, Some(step.parse::<u32>().map_err(|_| invalid())?)),
+                None => (part, None),
+            };
+            let (start, end) = if range == "*" || range == "?" {
+                (min, max)
+            } else if let Some((a, b)) = range.split_once('-') {
+                (value(a)?, value(b)?)
+            } else {
+                let v = value(range)?;
+                // `a/n`表示从a开始直到最大值
+                (v, if step.is_some() { max } else { v })
+            };
+            if step == Some(0) || start < min || end > max || start > end {
+                return Err(invalid());
+            }
+            let step = step.unwrap_or(1) as usize;
+            for v in (start..=end).step_by(step) {
+                bits |= 1 << v;
+            }
+        }
+        Ok(Field(bits))
+    }
+}
+
+/// cron表达式, 时间均按UTC计算
+///
+/// 支持5个字段`分 时 日 月 周`, 或在最前面加上秒的6个字段, 以及`@yearly`, `@monthly`, `@weekly`, `@daily`, `@hourly`。
+/// 月份及星期可使用英文缩写, 星期中0和7均表示周日, 日和周同时指定时满足其一即可触发
+///
+/// # Examples
+///
+/// ```
+/// use algorithm::CronSchedule;
+/// fn main() {
+///     // 每天3点, 2024-01-01 00:00:00 UTC
+///     let cron: CronSchedule = "0 3 * * *".parse().unwrap();
+///     let start = 1_704_067_200;
+///     assert_eq!(cron.next_after(start), Some(start + 3 * 3600));
+///     assert_eq!(cron.next_after(start + 3 * 3600), Some(start + 27 * 3600));
+///     // 每个工作日整点起每15分钟
+///     let cron = CronSchedule::parse("*/15 9-17 * * MON-FRI").unwrap();
+///     // 2024-01-06为周六, 下一次为周一的9点
+///     assert_eq!(cron.next_after(start + 5 * 86400), Some(start + 7 * 86400 + 9 * 3600));
+///     assert!(CronSchedule::parse("0 0 30 2 *").unwrap().next_after(start).is_none());
+///     assert!(CronSchedule::parse("61 * * * *").is_err());
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    seconds: Field,
+    minutes: Field,
+    hours: Field,
+    days: Field,
+    months: Field,
+    weekdays: Field,
+    /// 日及周是否为`*`, 均指定时满足其一即可
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expr: &str) -> Result<Self, CronError> {
+        let expr = match expr.trim() {
+            "@yearly" | "@annually" => "0 0 0 1 1 *",
+            "@monthly" => "0 0 0 1 * *",
+            "@weekly" => "0 0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 0 * * *",
+            "@hourly" => "0 0 * * * *",
+            expr => expr,
+        };
+        let mut fields: Vec<&str> = expr.split_whitespace().collect();
+        match fields.len() {
+            5 => fields.insert(0, "0"),
+            6 => {}
+            n => return Err(CronError::FieldCount(n)),
+        }
+        let mut weekdays = Field::parse(fields[5], 0, 7, &WEEK_NAMES, 0)?;
+        if weekdays.has(7) {
+            weekdays = Field((weekdays.0 | 1) & !(1 << 7));
+        }
+        let is_any = |f: &str| f == "*" || f == "?";
+        Ok(Self {
+            seconds: Field::parse(fields[0], 0, 59, &[], 0)?,
+            minutes: Field::parse(fields[1], 0, 59, &[], 0)?,
+            hours: Field::parse(fields[2], 0, 23, &[], 0)?,
+            days: Field::parse(fields[3], 1, 31, &[], 0)?,
+            months: Field::parse(fields[4], 1, 12, &MONTH_NAMES, 1)?,
+            weekdays,
+            any_day: is_any(fields[3]),
+            any_weekday: is_any(fields[5]),
+        })
+    }
+
+    fn day_matches(&self, day: u32, weekday: u32) -> bool {
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (false, true) => self.days.has(day),
+            (true, false) => self.weekdays.has(weekday),
+            (false, false) => self.days.has(day) || self.weekdays.has(weekday),
+        }
+    }
+
+    /// 严格晚于`timestamp`(秒)的下一次触发时间, 不会再触发时返回None
+    pub fn next_after(&self, timestamp: u64) -> Option<u64> {
+        let mut t = timestamp.checked_add(1)?;
+        let (limit, _, _) = civil_from_days((t / 86400) as i64);
+        loop {
+            let days = t / 86400;
+            let (year, month, day) = civil_from_days(days as i64);
+            if year > limit + MAX_YEARS {
+                return None;
+            }
+            if !self.months.has(month) {
+                let (year, month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+                t = days_from_civil(year, month, 1) as u64 * 86400;
+                continue;
+            }
+            // 1970-01-01为周四
+            let weekday = ((days + 4) % 7) as u32;
+            if !self.day_matches(day, weekday) {
+                t = (days + 1) * 86400;
+                continue;
+            }
+            let secs = (t % 86400) as u32;
+            let (hour, minute, second) = (secs / 3600, secs % 3600 / 60, secs % 60);
+            let base = days * 86400;
+            let Some(h) = self.hours.next(hour) else {
+                t = base + 86400;
+                continue;
+            };
+            if h != hour {
+                t = base + h as u64 * 3600;
+                continue;
+            }
+            let Some(m) = self.minutes.next(minute) else {
+                t = base + (hour as u64 + 1) * 3600;
+                continue;
+            };
+            if m != minute {
+                t = base + hour as u64 * 3600 + m as u64 * 60;
+                continue;
+            }
+            match self.seconds.next(second) {
+                Some(s) => return Some(base + hour as u64 * 3600 + minute as u64 * 60 + s as u64),
+                None => t = base + hour as u64 * 3600 + (minute as u64 + 1) * 60,
+            }
+        }
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = CronError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// 1970-01-01之后的天数转换为年月日
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+/// 年月日转换为1970-01-01之后的天数
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 } as i64;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// 按cron表达式重复触发的定时器, 通过`add_repeat`加入后每次触发会自动计算下一次的时间
+///
+/// 时间来源为[`get_milltimestamp`], 因此开启`sim` feature后可由`SimClock`控制。
+/// `when`为距离下次触发的毫秒数, 用于以毫秒为单位的[`super::TimerWheel`]或[`super::TimerRBTree`]
+#[derive(Debug, Clone)]
+pub struct CronTimer<V> {
+    schedule: CronSchedule,
+    /// 上一次计算出的触发时间, 避免定时器提前触发时在同一时刻重复触发
+    last: u64,
+    pub val: V,
+}
+
+impl<V> CronTimer<V> {
+    pub fn new(val: V, schedule: CronSchedule) -> Self {
+        Self {
+            schedule,
+            last: 0,
+            val,
+        }
+    }
+
+    pub fn schedule(&self) -> &CronSchedule {
+        &self.schedule
+    }
+
+    /// 给定当前的毫秒时间戳, 计算严格晚于该时间的下一次触发的毫秒时间戳
+    ///
+    /// ```
+    /// use algorithm::CronTimer;
+    /// fn main() {
+    ///     let timer = CronTimer::new((), "0 */10 * * * *".parse().unwrap());
+    ///     assert_eq!(timer.next_fire(1_000), Some(600_000));
+    ///     assert_eq!(timer.next_fire(600_000), Some(1_200_000));
+    /// }
+    /// ```
+    pub fn next_fire(&self, now: u64) -> Option<u64> {
+        self.schedule
+            .next_after(now / 1000)
+            .and_then(|secs| secs.checked_mul(1000))
+    }
+
+}
+
+impl<V> Timer for CronTimer<V> {
+    fn when(&self) -> u64 {
+        let now = get_milltimestamp();
+        when_from(now, self.next_fire(now.max(self.last)))
+    }
+
+    fn when_mut(&mut self) -> u64 {
+        let now = get_milltimestamp();
+        let next = self.next_fire(now.max(self.last));
+        if let Some(next) = next {
+            self.last = next;
+        }
+        when_from(now, next)
+    }
+}
+
+/// 距离下次触发的毫秒数, 不再触发时为u64::MAX
+fn when_from(now: u64, next: Option<u64>) -> u64 {
+    next.map_or(u64::MAX, |next| next.saturating_sub(now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{civil_from_days, days_from_civil, CronSchedule};
+
+    /// 逐分钟检查是否满足表达式
+    fn brute_next(cron: &CronSchedule, timestamp: u64) -> Option<u64> {
+        let mut t = (timestamp / 60 + 1) * 60;
+        // 2月29日最多相隔8年
+        for _ in 0..60 * 24 * 366 * 8 {
+            let days = t / 86400;
+            let (_, month, day) = civil_from_days(days as i64);
+            let secs = (t % 86400) as u32;
+            if cron.months.has(month)
+                && cron.day_matches(day, ((days + 4) % 7) as u32)
+                && cron.hours.has(secs / 3600)
+                && cron.minutes.has(secs % 3600 / 60)
+            {
+                return Some(t);
+            }
+            t += 60;
+        }
+        None
+    }
+
+    #[test]
+    fn test_civil() {
+        for days in -800_000..800_000i64 {
+            let (y, m, d) = civil_from_days(days);
+            assert_eq!(days_from_civil(y, m, d), days);
+        }
+        assert_eq!(civil_from_days(19_723), (2024, 1, 1));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+    }
+
+    #[test]
+    fn test_next_after() {
+        let exprs = [
+            "*/7 * * * *",
+            "5 4 * * SUN",
+            "0 0 29 2 *",
+            "30 9-17/2 1,15 * MON",
+            "15 3 31 * *",
+            "0 0 * JUN-AUG 6,7",
+        ];
+        // 2023-12-31 23:59:30 UTC
+        let start = 1_704_067_170u64;
+        for expr in exprs {
+            let cron = CronSchedule::parse(expr).unwrap();
+            let mut t = start;
+            for _ in 0..3 {
+                let next = cron.next_after(t);
+                assert_eq!(next, brute_next(&cron, t), "{}", expr);
+                t = next.unwrap();
+            }
+        }
+        for expr in ["0 12 L * *", "* * * *", "0 0 0 13 *", "*/0 * * * *", "5-1 * * * *"] {
+            assert!(CronSchedule::parse(expr).is_err(), "{}", expr);
+        }
+    }
+}
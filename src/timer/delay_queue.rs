@@ -0,0 +1,399 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError},
+    task::{Context, Poll, Waker},
+    thread,
+    time::Duration,
+};
+
+use super::{InstantSource, TickSource, Timer, TimerRBTree};
+
+/// [`DelayQueue::push`]返回的句柄, 可用于取消该元素
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DelayKey(u64);
+
+struct DelayEntry<T> {
+    /// 到期时间, 为时间来源起点之后的纳秒数
+    when: u64,
+    val: T,
+}
+
+impl<T> Timer for DelayEntry<T> {
+    fn when(&self) -> u64 {
+        self.when
+    }
+}
+
+struct State<T> {
+    tree: TimerRBTree<DelayEntry<T>>,
+    /// 等待中的异步消费者, 以[`Pop`]的编号区分
+    wakers: Vec<(u64, Waker)>,
+    next_waker: u64,
+    /// 是否已启动唤醒异步消费者的线程
+    notifier: bool,
+    closed: bool,
+}
+
+impl<T> State<T> {
+    fn pop_due(&mut self, now: u64) -> Option<T> {
+        self.tree.poll_expired(now).map(|(_, entry)| entry.val)
+    }
+
+    /// 距离队首元素到期的时间, 为空时返回None
+    fn next_delay(&self, now: u64) -> Option<Duration> {
+        self.tree
+            .tick_first()
+            .map(|when| Duration::from_nanos(when.saturating_sub(now)))
+    }
+}
+
+struct Shared<T> {
+    source: Box<dyn TickSource>,
+    state: Mutex<State<T>>,
+    cond: Condvar,
+}
+
+impl<T> Shared<T> {
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn now(&self) -> u64 {
+        self.source.elapsed().as_nanos() as u64
+    }
+
+    fn deadline(&self, delay: Duration) -> u64 {
+        self.now()
+            .saturating_add(delay.as_nanos().min(u64::MAX as u128) as u64)
+    }
+
+    /// 等待通知, `timeout`为None时一直等待
+    fn wait<'a>(
+        &self,
+        state: MutexGuard<'a, State<T>>,
+        timeout: Option<Duration>,
+    ) -> MutexGuard<'a, State<T>> {
+        match timeout {
+            Some(timeout) => {
+                self.cond
+                    .wait_timeout(state, timeout)
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .0
+            }
+            None => self.cond.wait(state).unwrap_or_else(PoisonError::into_inner),
+        }
+    }
+
+    /// 在元素到期时唤醒等待中的异步消费者, 队列析构后退出
+    fn notify_loop(&self) {
+        let mut state = self.lock();
+        loop {
+            if state.closed {
+                return;
+            }
+            if state.wakers.is_empty() {
+                state = self.wait(state, None);
+                continue;
+            }
+            match state.next_delay(self.now()) {
+                Some(delay) if delay.is_zero() => {
+                    let wakers = std::mem::take(&mut state.wakers);
+                    drop(state);
+                    // 在锁外唤醒, 避免唤醒时重入
+                    for (_, waker) in wakers {
+                        waker.wake();
+                    }
+                    state = self.lock();
+                }
+                delay => state = self.wait(state, delay),
+            }
+        }
+    }
+}
+
+/// 基于[`TimerRBTree`]的延时队列, 元素只有在到期后才能取出, 类似Java的DelayQueue
+///
+/// 生产者通过[`DelayQueue::push`]放入元素, 消费者可阻塞等待([`DelayQueue::pop_blocking`], [`DelayQueue::pop_timeout`]),
+/// 也可通过[`DelayQueue::pop`]异步等待, 队列可放入`Arc`中在多线程间共享。
+/// 默认以系统单调时钟计时, 可通过[`DelayQueue::with_source`]替换时间来源
+///
+/// # Examples
+///
+/// ```
+/// use algorithm::DelayQueue;
+/// use std::sync::Arc;
+/// use std::thread;
+/// use std::time::Duration;
+/// fn main() {
+///     let queue = Arc::new(DelayQueue::new());
+///     let producer = queue.clone();
+///     thread::spawn(move || {
+///         producer.push("later", Duration::from_millis(20));
+///         producer.push("sooner", Duration::from_millis(5));
+///         let key = producer.push("cancelled", Duration::from_secs(60));
+///         producer.cancel(key);
+///     });
+///     assert_eq!(queue.pop_blocking(), "sooner");
+///     assert_eq!(queue.pop_blocking(), "later");
+///     assert_eq!(queue.pop_timeout(Duration::from_millis(10)), None);
+/// }
+/// ```
+pub struct DelayQueue<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T: Send + 'static> DelayQueue<T> {
+    /// 以系统单调时钟创建延时队列
+    pub fn new() -> Self {
+        Self::with_source(InstantSource::new())
+    }
+
+    /// 以指定的时间来源创建延时队列
+    pub fn with_source<S: TickSource>(source: S) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                source: Box::new(source),
+                state: Mutex::new(State {
+                    tree: TimerRBTree::new(),
+                    wakers: vec![],
+                    next_waker: 0,
+                    notifier: false,
+                    closed: false,
+                }),
+                cond: Condvar::new(),
+            }),
+        }
+    }
+
+    /// 放入元素, 在`delay`之后到期, 返回可用于取消的句柄
+    pub fn push(&self, val: T, delay: Duration) -> DelayKey {
+        let when = self.shared.deadline(delay);
+        let id = self.shared.lock().tree.add_timer_at(DelayEntry { when, val }, when);
+        // 新元素可能早于原队首, 等待中的消费者需重新计算等待时间
+        self.shared.cond.notify_all();
+        DelayKey(id)
+    }
+
+    /// 取消还未取出的元素, 返回该元素
+    ///
+    /// ```
+    /// use algorithm::DelayQueue;
+    /// use std::time::Duration;
+    /// fn main() {
+    ///     let queue = DelayQueue::new();
+    ///     let key = queue.push(1, Duration::from_secs(60));
+    ///     assert_eq!(queue.len(), 1);
+    ///     assert_eq!(queue.cancel(key), Some(1));
+    ///     assert_eq!(queue.cancel(key), None);
+    ///     assert!(queue.is_empty());
+    /// }
+    /// ```
+    pub fn cancel(&self, key: DelayKey) -> Option<T> {
+        self.shared.lock().tree.del_timer(key.0).map(|e| e.val)
+    }
+
+    /// 队列中的元素数量, 包括未到期的
+    pub fn len(&self) -> usize {
+        self.shared.lock().tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 距离队首元素到期的时间, 为空时返回None
+    pub fn next_delay(&self) -> Option<Duration> {
+        self.shared.lock().next_delay(self.shared.now())
+    }
+
+    /// 取出一个已到期的元素, 没有到期的元素时立即返回None
+    ///
+    /// ```
+    /// use algorithm::DelayQueue;
+    /// use std::time::Duration;
+    /// fn main() {
+    ///     let queue = DelayQueue::new();
+    ///     queue.push(1, Duration::ZERO);
+    ///     queue.push(2, Duration::from_secs(60));
+    ///     assert_eq!(queue.try_pop(), Some(1));
+    ///     assert_eq!(queue.try_pop(), None);
+    ///     assert_eq!(queue.len(), 1);
+    /// }
+    /// ```
+    pub fn try_pop(&self) -> Option<T> {
+        self.shared.lock().pop_due(self.shared.now())
+    }
+
+    /// 阻塞等待直到有元素到期
+    pub fn pop_blocking(&self) -> T {
+        let mut state = self.shared.lock();
+        loop {
+            let now = self.shared.now();
+            if let Some(val) = state.pop_due(now) {
+                return val;
+            }
+            let delay = state.next_delay(now);
+            state = self.shared.wait(state, delay);
+        }
+    }
+
+    /// 最多等待`timeout`的时间, 超时仍没有元素到期则返回None
+    pub fn pop_timeout(&self, timeout: Duration) -> Option<T> {
+        let deadline = self.shared.deadline(timeout);
+        let mut state = self.shared.lock();
+        loop {
+            let now = self.shared.now();
+            if let Some(val) = state.pop_due(now) {
+                return Some(val);
+            }
+            if now >= deadline {
+                return None;
+            }
+            let remain = Duration::from_nanos(deadline - now);
+            let wait = state.next_delay(now).map_or(remain, |delay| delay.min(remain));
+            state = self.shared.wait(state, Some(wait));
+        }
+    }
+
+    /// 异步等待直到有元素到期, 不依赖任何异步运行时
+    ///
+    /// 首次异步等待时会启动一个后台线程, 在元素到期时唤醒等待中的消费者
+    ///
+    /// ```
+    /// use algorithm::DelayQueue;
+    /// use std::future::Future;
+    /// use std::pin::pin;
+    /// use std::sync::{atomic::{AtomicBool, Ordering}, Arc};
+    /// use std::task::{Context, Poll, Wake, Waker};
+    /// use std::time::Duration;
+    ///
+    /// struct Flag(AtomicBool);
+    /// impl Wake for Flag {
+    ///     fn wake(self: Arc<Self>) {
+    ///         self.0.store(true, Ordering::SeqCst);
+    ///     }
+    /// }
+    ///
+    /// fn main() {
+    ///     let queue = DelayQueue::new();
+    ///     queue.push(7, Duration::from_millis(5));
+    ///     let flag = Arc::new(Flag(AtomicBool::new(false)));
+    ///     let waker = Waker::from(flag.clone());
+    ///     let mut cx = Context::from_waker(&waker);
+    ///     let mut pop = pin!(queue.pop());
+    ///     assert!(pop.as_mut().poll(&mut cx).is_pending());
+    ///     // 到期后由后台线程唤醒
+    ///     while !flag.0.load(Ordering::SeqCst) {
+    ///         std::thread::sleep(Duration::from_millis(1));
+    ///     }
+    ///     assert_eq!(pop.as_mut().poll(&mut cx), Poll::Ready(7));
+    /// }
+    /// ```
+    pub fn pop(&self) -> Pop<'_, T> {
+        Pop {
+            queue: self,
+            id: None,
+        }
+    }
+
+    fn poll_pop(&self, cx: &mut Context<'_>, id: &mut Option<u64>) -> Poll<T> {
+        let mut state = self.shared.lock();
+        if let Some(val) = state.pop_due(self.shared.now()) {
+            if let Some(id) = id.take() {
+                state.wakers.retain(|(i, _)| *i != id);
+            }
+            return Poll::Ready(val);
+        }
+        let id = *id.get_or_insert_with(|| {
+            state.next_waker += 1;
+            state.next_waker
+        });
+        match state.wakers.iter_mut().find(|(i, _)| *i == id) {
+            Some((_, waker)) => waker.clone_from(cx.waker()),
+            None => state.wakers.push((id, cx.waker().clone())),
+        }
+        if !state.notifier {
+            state.notifier = true;
+            let shared = self.shared.clone();
+            thread::spawn(move || shared.notify_loop());
+        }
+        self.shared.cond.notify_all();
+        Poll::Pending
+    }
+}
+
+impl<T: Send + 'static> Default for DelayQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for DelayQueue<T> {
+    fn drop(&mut self) {
+        // 通知后台线程退出
+        self.shared.lock().closed = true;
+        self.shared.cond.notify_all();
+    }
+}
+
+/// [`DelayQueue::pop`]返回的future, 析构时移除已注册的唤醒器
+pub struct Pop<'a, T> {
+    queue: &'a DelayQueue<T>,
+    /// 注册唤醒器时分配的编号
+    id: Option<u64>,
+}
+
+impl<T: Send + 'static> Future for Pop<'_, T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let this = self.get_mut();
+        this.queue.poll_pop(cx, &mut this.id)
+    }
+}
+
+impl<T> Drop for Pop<'_, T> {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            self.queue.shared.lock().wakers.retain(|(i, _)| *i != id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::Future,
+        pin::pin,
+        sync::Arc,
+        task::{Context, Wake, Waker},
+        time::Duration,
+    };
+
+    use super::DelayQueue;
+
+    struct Noop;
+
+    impl Wake for Noop {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    #[test]
+    fn test_drop_pop() {
+        let queue = DelayQueue::new();
+        queue.push(1, Duration::from_secs(60));
+        let waker = Waker::from(Arc::new(Noop));
+        let mut cx = Context::from_waker(&waker);
+        {
+            let mut first = pin!(queue.pop());
+            let mut second = pin!(queue.pop());
+            assert!(first.as_mut().poll(&mut cx).is_pending());
+            assert!(second.as_mut().poll(&mut cx).is_pending());
+            // 重复poll不会重复注册
+            assert!(first.as_mut().poll(&mut cx).is_pending());
+            assert_eq!(queue.shared.lock().wakers.len(), 2);
+        }
+        assert!(queue.shared.lock().wakers.is_empty());
+    }
+}
@@ -9,6 +9,72 @@ pub trait Timer {
     }
 }
 
+/// 周期定时器的重复信息, 触发后以同一个id重新加入定时器
+pub(crate) struct Repeat<T> {
+    /// 固定的重复周期, None表示每次触发后由[`Timer::when_mut`]重新计算下次的触发时间
+    pub period: Option<u64>,
+    /// 剩余的触发次数, None表示无限重复
+    pub times: Option<u64>,
+    /// 触发时复制一份值返回, 原值继续留在定时器中
+    pub clone: fn(&T) -> T,
+}
+
+impl<T> Repeat<T> {
+    pub fn new(period: u64, times: Option<u64>) -> Self
+    where
+        T: Clone,
+    {
+        debug_assert!(times != Some(0), "重复次数必须大于0");
+        Self {
+            period: Some(period.max(1)),
+            times,
+            clone: T::clone,
+        }
+    }
+
+    /// 由值自身计算下次触发时间的重复, 如[`CronTimer`]
+    pub fn by_when() -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            period: None,
+            times: None,
+            clone: T::clone,
+        }
+    }
+
+    /// 恢复导出的重复信息
+    pub fn restore(pending: PendingRepeat) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            period: pending.period.map(|period| period.max(1)),
+            times: pending.times,
+            clone: T::clone,
+        }
+    }
+
+    pub fn export(&self) -> PendingRepeat {
+        PendingRepeat {
+            period: self.period,
+            times: self.times,
+        }
+    }
+
+    /// 触发一次, 返回是否还需要继续重复
+    pub fn fire(&mut self) -> bool {
+        match &mut self.times {
+            Some(times) => {
+                *times = times.saturating_sub(1);
+                *times > 0
+            }
+            None => true,
+        }
+    }
+}
+
 macro_rules! impl_primitive_timer {
     ($ty:ident) => {
         impl Timer for $ty {
@@ -38,8 +104,22 @@ mod timer_rbtree;
 mod timer_wheel;
 mod stamp_timer;
 mod step_timer;
+mod timer_service;
+mod async_timer;
+#[cfg(feature = "sim")]
+mod sim_clock;
+mod timer_queue;
+mod delay_queue;
+mod cron;
 
-pub use timer_wheel::TimerWheel;
+pub use timer_wheel::{TimerWheel, TimerWheelBuilder, TimerWheelError};
 pub use timer_rbtree::TimerRBTree;
 pub use stamp_timer::StampTimer;
 pub use step_timer::StepTimer;
+pub use timer_service::{TimerHandle, TimerService};
+pub use timer_queue::{DrainExpired, PendingRepeat, PendingTimer, TimerQueue};
+pub use delay_queue::{DelayKey, DelayQueue, Pop};
+pub use cron::{CronError, CronSchedule, CronTimer};
+#[cfg(feature = "sim")]
+pub use sim_clock::{SimClock, SimEvent, SimHandle, SimTimer};
+pub use async_timer::{AsyncTimer, Elapsed, InstantSource, Interval, Sleep, TickSource, Timeout};
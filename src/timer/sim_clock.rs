@@ -0,0 +1,228 @@
+use std::{
+    cell::{Ref, RefCell, RefMut},
+    fmt,
+    rc::Rc,
+};
+
+use crate::set_virtual_milltimestamp;
+
+use super::{Timer, TimerRBTree, TimerWheel};
+
+/// 可由[`SimClock`]驱动的定时器, 时间单位为毫秒
+pub trait SimTimer {
+    /// 定时器触发时返回的值
+    type Value;
+
+    /// 递进`delta`毫秒, 到期的定时器以`(id, 值)`回调
+    fn sim_advance(&mut self, delta: u64, f: &mut dyn FnMut(u64, Self::Value));
+}
+
+impl<T: Timer> SimTimer for TimerWheel<T> {
+    type Value = T;
+
+    fn sim_advance(&mut self, delta: u64, f: &mut dyn FnMut(u64, T)) {
+        for (id, val) in self.update_deltatime(delta).unwrap_or_default() {
+            f(id, val);
+        }
+    }
+}
+
+impl<T: Timer> SimTimer for TimerRBTree<T> {
+    type Value = T;
+
+    fn sim_advance(&mut self, delta: u64, f: &mut dyn FnMut(u64, T)) {
+        for (id, val) in self.update_deltatime(delta).unwrap_or_default() {
+            f(id, val);
+        }
+    }
+}
+
+/// 模拟时钟记录的触发事件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimEvent<V> {
+    /// 触发时的虚拟时间(毫秒)
+    pub at: u64,
+    /// 挂载定时器时的名字
+    pub source: &'static str,
+    pub timer_id: u64,
+    /// 定时器触发时返回的值
+    pub value: V,
+}
+
+/// 挂载的定时器及其事件日志
+struct SimSlot<T: SimTimer> {
+    source: &'static str,
+    timer: T,
+    events: Vec<SimEvent<T::Value>>,
+}
+
+/// 擦除定时器类型后由时钟统一递进
+trait SimDrive {
+    /// 递进`delta`毫秒, 返回本次触发的事件数量
+    fn drive(&mut self, now: u64, delta: u64) -> usize;
+}
+
+impl<T: SimTimer> SimDrive for SimSlot<T> {
+    fn drive(&mut self, now: u64, delta: u64) -> usize {
+        let (source, events) = (self.source, &mut self.events);
+        let start = events.len();
+        self.timer.sim_advance(delta, &mut |timer_id, value| {
+            events.push(SimEvent {
+                at: now,
+                source,
+                timer_id,
+                value,
+            })
+        });
+        events.len() - start
+    }
+}
+
+/// 挂载到[`SimClock`]的定时器句柄, 可在两次递进之间访问定时器及其触发日志,
+/// 递进时句柄不能处于借用状态
+pub struct SimHandle<T: SimTimer> {
+    slot: Rc<RefCell<SimSlot<T>>>,
+}
+
+impl<T: SimTimer> SimHandle<T> {
+    /// 挂载时的名字
+    pub fn source(&self) -> &'static str {
+        self.slot.borrow().source
+    }
+
+    /// 借用定时器
+    pub fn borrow(&self) -> Ref<'_, T> {
+        Ref::map(self.slot.borrow(), |s| &s.timer)
+    }
+
+    /// 可变借用定时器, 如在两次递进之间添加定时器
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        RefMut::map(self.slot.borrow_mut(), |s| &mut s.timer)
+    }
+
+    /// 该定时器已记录的事件, 按触发顺序排列
+    pub fn events(&self) -> Ref<'_, [SimEvent<T::Value>]> {
+        Ref::map(self.slot.borrow(), |s| &s.events[..])
+    }
+
+    /// 取出该定时器已记录的事件并清空日志
+    pub fn take_events(&self) -> Vec<SimEvent<T::Value>> {
+        std::mem::take(&mut self.slot.borrow_mut().events)
+    }
+}
+
+impl<T: SimTimer> Clone for SimHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            slot: self.slot.clone(),
+        }
+    }
+}
+
+impl<T: SimTimer> fmt::Debug for SimHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SimHandle")
+            .field("source", &self.source())
+            .field("events", &self.slot.borrow().events.len())
+            .finish()
+    }
+}
+
+/// 确定性的模拟时钟, 用于编写可复现的超时场景测试, 需开启`sim` feature
+///
+/// 创建后当前线程的[`crate::get_milltimestamp`]及[`crate::get_timestamp`]均返回虚拟时间,
+/// 带ttl的缓存将按虚拟时间过期, 析构后恢复真实时间。
+/// 未开启`sim` feature时获取时间戳不会检查虚拟时间, 不影响正式环境的性能。
+///
+/// 挂载的定时器由时钟持有, 随虚拟时间一起递进, 触发的定时器连同原始的值记录在各自的事件日志中,
+/// 挂载时返回的[`SimHandle`]可在两次递进之间访问定时器及日志。
+///
+/// 缓存的清理时间在创建时确定, 应在创建模拟时钟之后再创建缓存
+///
+/// # Examples
+///
+/// ```
+/// use algorithm::{LruCache, SimClock, TimerRBTree, TimerWheel};
+/// fn main() {
+///     let mut clock = SimClock::new(0);
+///     let mut cache = LruCache::new(10);
+///     cache.insert_with_ttl("session", 1, 2);
+///     let mut wheel = TimerWheel::new();
+///     wheel.append_timer_wheel(1000, "MillisWheel");
+///     wheel.append_timer_wheel(60, "SecondWheel");
+///     wheel.add_timer(1500u64);
+///     let wheel = clock.attach("wheel", wheel);
+///     let tree = clock.attach("tree", TimerRBTree::new());
+///     clock.run(2000, 500);
+///     // 在两次递进之间添加定时器
+///     tree.borrow_mut().add_timer(500u64);
+///     assert_eq!(clock.advance(500), 1);
+///     assert_eq!(clock.run(500, 100), 0);
+///     assert_eq!(cache.get(&"session"), None);
+///     let log: Vec<_> = wheel.events().iter().map(|e| (e.at, e.source, e.value)).collect();
+///     assert_eq!(log, vec![(1500, "wheel", 1500)]);
+///     assert_eq!(tree.take_events()[0].at, 2500);
+///     assert!(wheel.borrow().is_empty() && tree.borrow().is_empty());
+/// }
+/// ```
+pub struct SimClock {
+    now: u64,
+    prev: Option<u64>,
+    timers: Vec<Rc<RefCell<dyn SimDrive>>>,
+}
+
+impl SimClock {
+    /// 创建模拟时钟, 虚拟时间从`start`毫秒开始
+    pub fn new(start: u64) -> Self {
+        let prev = set_virtual_milltimestamp(Some(start));
+        Self {
+            now: start,
+            prev,
+            timers: vec![],
+        }
+    }
+
+    /// 当前的虚拟时间(毫秒)
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+
+    /// 挂载定时器, 之后随时钟一起递进, 触发的值按原始类型记录在返回的句柄中
+    pub fn attach<T: SimTimer + 'static>(&mut self, source: &'static str, timer: T) -> SimHandle<T> {
+        let slot = Rc::new(RefCell::new(SimSlot {
+            source,
+            timer,
+            events: vec![],
+        }));
+        self.timers.push(slot.clone());
+        SimHandle { slot }
+    }
+
+    /// 虚拟时间前进`delta`毫秒, 并递进所有挂载的定时器, 返回本次触发的事件数量
+    pub fn advance(&mut self, delta: u64) -> usize {
+        self.now = self.now.saturating_add(delta);
+        set_virtual_milltimestamp(Some(self.now));
+        self.timers
+            .iter()
+            .map(|timer| timer.borrow_mut().drive(self.now, delta))
+            .sum()
+    }
+
+    /// 以`step`为步长逐步前进`total`毫秒, 步长越小记录的触发时间越精确, 返回触发的事件数量
+    pub fn run(&mut self, total: u64, step: u64) -> usize {
+        let step = step.max(1);
+        let (mut passed, mut fired) = (0, 0);
+        while passed < total {
+            let delta = step.min(total - passed);
+            fired += self.advance(delta);
+            passed += delta;
+        }
+        fired
+    }
+}
+
+impl Drop for SimClock {
+    fn drop(&mut self) {
+        set_virtual_milltimestamp(self.prev);
+    }
+}
@@ -1,7 +1,11 @@
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::Duration;
+
+use crate::get_milltimestamp;
 
 use super::Timer;
 
+/// 以时间戳为时间线的定时器, `when`为到期的时间戳(秒或毫秒),
+/// 定时器的时间线为时间戳时通过`add_timer_at(val, val.when())`加入
 pub struct StampTimer<T> {
     duration: Duration,
     is_sec: bool,
@@ -33,11 +37,12 @@ impl<T> StampTimer<T> {
 
 impl<T> Timer for StampTimer<T> {
     fn when(&self) -> u64 {
-        let when = SystemTime::now() + self.duration;
+        // 经由get_milltimestamp获取时间, 以便受模拟时钟控制
+        let when = get_milltimestamp().saturating_add(self.duration.as_millis() as u64);
         if self.is_sec {
-            when.duration_since(UNIX_EPOCH).unwrap().as_secs()
+            when / 1000
         } else {
-            when.duration_since(UNIX_EPOCH).unwrap().as_millis() as u64
+            when
         }
     }
 }
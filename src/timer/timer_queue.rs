@@ -0,0 +1,394 @@
+use std::marker::PhantomData;
+
+use super::{Timer, TimerRBTree, TimerWheel};
+
+/// [`TimerWheel`]与[`TimerRBTree`]共同的定时器接口, 调度器可依此在两种实现间切换
+///
+/// 两者共享同一条时间线: 从0开始, 随`advance_to`/`update_deltatime`递进, 当前时间由[`TimerQueue::now`]获取。
+/// 接口中所有的时间统一按以下含义处理, 与具体实现无关:
+///
+/// * 添加及重设定时器时, 值的[`Timer::when`]及`reset_timer`的`when`为相对当前时间的延时,
+///   需要指定时间线上的触发时间时使用`add_timer_at`/`reset_timer_at`
+/// * `advance_to`, `poll_expired`, `drain_expired`的参数及`tick_first`的返回值为时间线上的时间
+/// * `update_deltatime`, `postpone`的参数及`next_delay`的返回值为相对当前时间的时长
+///
+/// 延时为0或触发时间不晚于当前时间的定时器, 时轮在下一个刻度触发, 红黑树在下一次递进时触发。
+///
+/// 两者的固有方法与接口的含义一致, 仅时轮的[`TimerWheel::update_now`]保持原有的含义, 以距离上次递进的时间为参数。
+///
+/// # Examples
+///
+/// ```
+/// use algorithm::{TimerQueue, TimerRBTree, TimerWheel};
+///
+/// fn drive<Q: TimerQueue<u64>>(queue: &mut Q) -> Vec<(u64, u64)> {
+///     // 时间线上的10时加入, 延时均相对10计算
+///     queue.advance_to(10);
+///     for v in [90, 30, 150] {
+///         queue.add_timer(v);
+///     }
+///     queue.add_timer_at(0, 50);
+///     let mut fired = vec![];
+///     while let Some(at) = queue.tick_first() {
+///         for (_, v) in queue.advance_to(at).unwrap_or_default() {
+///             fired.push((queue.now(), v));
+///         }
+///     }
+///     fired
+/// }
+///
+/// fn main() {
+///     let mut wheel = TimerWheel::new();
+///     wheel.append_timer_wheel(60, "SecondWheel");
+///     wheel.append_timer_wheel(60, "MinuteWheel");
+///     let expect = vec![(40, 30), (50, 0), (100, 90), (160, 150)];
+///     assert_eq!(drive(&mut wheel), expect);
+///     assert_eq!(drive(&mut TimerRBTree::new()), expect);
+/// }
+/// ```
+pub trait TimerQueue<T: Timer> {
+    /// 定时器的数量
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 清除所有的定时器
+    fn clear(&mut self);
+
+    /// 当前在时间线上的时间
+    fn now(&self) -> u64;
+
+    /// 添加在当前时间的[`Timer::when`]之后触发的定时器, 返回分配的id
+    fn add_timer(&mut self, mut val: T) -> u64 {
+        let at = self.now().saturating_add(val.when_mut());
+        self.add_timer_at(val, at)
+    }
+
+    /// 以指定的id添加在当前时间的[`Timer::when`]之后触发的定时器, 该id已存在时替换原有的定时器
+    fn add_timer_by_id(&mut self, timer_id: u64, mut val: T) {
+        let at = self.now().saturating_add(val.when_mut());
+        self.add_timer_by_id_at(timer_id, val, at)
+    }
+
+    /// 添加在时间线上`at`时触发的定时器, 返回分配的id
+    fn add_timer_at(&mut self, val: T, at: u64) -> u64;
+
+    /// 以指定的id添加在时间线上`at`时触发的定时器, 该id已存在时替换原有的定时器
+    fn add_timer_by_id_at(&mut self, timer_id: u64, val: T, at: u64);
+
+    /// 删除指定的定时器
+    fn del_timer(&mut self, timer_id: u64) -> Option<T>;
+
+    fn get_timer(&self, timer_id: &u64) -> Option<&T>;
+
+    fn get_mut_timer(&mut self, timer_id: &u64) -> Option<&mut T>;
+
+    /// 将定时器的触发时间重新设置为当前时间之后的`when`, 定时器不存在时返回false
+    fn reset_timer(&mut self, timer_id: u64, when: u64) -> bool {
+        let at = self.now().saturating_add(when);
+        self.reset_timer_at(timer_id, at)
+    }
+
+    /// 将定时器的触发时间重新设置为时间线上的`at`, 定时器不存在时返回false
+    fn reset_timer_at(&mut self, timer_id: u64, at: u64) -> bool;
+
+    /// 将定时器的触发时间延后`delta`, 定时器不存在时返回false
+    fn postpone(&mut self, timer_id: u64, delta: u64) -> bool;
+
+    /// 最早到期的定时器在时间线上的触发时间, 为空时返回None
+    fn tick_first(&self) -> Option<u64>;
+
+    /// 距离最早到期的定时器触发还需递进的时间, 为空时返回None
+    fn next_delay(&self) -> Option<u64>;
+
+    /// 递进到时间线上的`now`, 返回到期的定时器, 返回None或空表示没有定时器到期
+    fn advance_to(&mut self, now: u64) -> Option<Vec<(u64, T)>>;
+
+    /// 递进`delta`的时间, 返回到期的定时器, 返回None或空表示没有定时器到期
+    fn update_deltatime(&mut self, delta: u64) -> Option<Vec<(u64, T)>>;
+
+    /// 递进到时间线上的`now`并取出一个到期的定时器, 不分配内存, 没有到期的定时器时返回None
+    fn poll_expired(&mut self, now: u64) -> Option<(u64, T)>;
+
+    /// 递进到时间线上的`now`, 返回逐个取出到期定时器的迭代器
+    ///
+    /// 提前停止迭代时剩余的到期定时器保留在队列中, 可用于限制每次处理的数量
+    ///
+    /// ```
+    /// use algorithm::{TimerQueue, TimerRBTree};
+    /// fn main() {
+    ///     let mut tree = TimerRBTree::new();
+    ///     for v in 1..=10u64 {
+    ///         tree.add_timer(v);
+    ///     }
+    ///     let mut now = 10;
+    ///     let mut rounds = 0;
+    ///     // 每轮最多处理4个
+    ///     while TimerQueue::drain_expired(&mut tree, now).take(4).count() > 0 {
+    ///         rounds += 1;
+    ///         now += 1;
+    ///     }
+    ///     assert_eq!(rounds, 3);
+    ///     assert!(tree.is_empty());
+    /// }
+    /// ```
+    fn drain_expired(&mut self, now: u64) -> DrainExpired<'_, Self, T>
+    where
+        Self: Sized,
+    {
+        DrainExpired::new(self, now)
+    }
+
+    /// 导出所有待触发的定时器, 按剩余时间排序, 周期定时器同时导出重复信息
+    fn export_timers(&self) -> Vec<PendingTimer<T>>
+    where
+        T: Clone;
+
+    /// 恢复导出的定时器, 保留原有的id, 以剩余时间重新加入, 周期定时器恢复后继续重复
+    ///
+    /// 所有的id须在`1..=max_timer_id`的范围内, 否则不做任何修改并返回超出范围的id
+    fn restore_timers<I>(&mut self, timers: I) -> Result<(), u64>
+    where
+        T: Clone,
+        I: IntoIterator<Item = PendingTimer<T>>;
+
+    /// 递进到时间线上的`now`, 到期的定时器交由回调处理,
+    /// 回调返回`Some`时以返回的id重新添加, 与[`TimerQueue::add_timer_by_id`]一样以值的[`Timer::when`]为延时
+    fn advance_to_with_callback<F>(&mut self, now: u64, f: &mut F)
+    where
+        F: FnMut(&mut Self, u64, T) -> Option<(u64, T)>,
+    {
+        if let Some(result) = self.advance_to(now) {
+            let mut collect_result = vec![];
+            for (timer_id, val) in result.into_iter() {
+                if let Some(v) = (*f)(self, timer_id, val) {
+                    collect_result.push(v);
+                }
+            }
+            for (timer_id, val) in collect_result.drain(..) {
+                TimerQueue::add_timer_by_id(self, timer_id, val);
+            }
+        }
+    }
+
+    /// 递进`delta`的时间, 到期的定时器交由回调处理, 见[`TimerQueue::advance_to_with_callback`]
+    fn update_deltatime_with_callback<F>(&mut self, delta: u64, f: &mut F)
+    where
+        F: FnMut(&mut Self, u64, T) -> Option<(u64, T)>,
+    {
+        let now = self.now().saturating_add(delta);
+        self.advance_to_with_callback(now, f)
+    }
+}
+
+/// 导出的待触发定时器, 重启后可通过[`TimerQueue::restore_timers`]恢复
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PendingTimer<T> {
+    pub timer_id: u64,
+    /// 距离触发还需递进的时间
+    pub remain: u64,
+    pub val: T,
+    /// 周期定时器的重复信息, None表示一次性的定时器
+    pub repeat: Option<PendingRepeat>,
+}
+
+/// 导出的周期定时器的重复信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PendingRepeat {
+    /// 固定的重复周期, None表示每次触发后由[`Timer::when_mut`]重新计算下次的触发时间
+    pub period: Option<u64>,
+    /// 包括下一次在内剩余的触发次数, None表示无限重复
+    pub times: Option<u64>,
+}
+
+/// 校验恢复的id均在`1..=max_timer_id`的范围内, 返回恢复后下一个分配的id, 超出范围时返回该id
+pub(crate) fn restore_next_id<T>(
+    timers: &[PendingTimer<T>],
+    next_timer_id: u64,
+    max_timer_id: u64,
+) -> Result<u64, u64> {
+    if let Some(p) = timers
+        .iter()
+        .find(|p| p.timer_id == 0 || p.timer_id > max_timer_id)
+    {
+        return Err(p.timer_id);
+    }
+    // 新分配的id从恢复的最大id之后开始, 避免与重启前已分配的id混淆
+    Ok(match timers.iter().map(|p| p.timer_id).max() {
+        Some(max) if max >= next_timer_id => {
+            if max >= max_timer_id {
+                1
+            } else {
+                max + 1
+            }
+        }
+        _ => next_timer_id,
+    })
+}
+
+/// [`TimerQueue::drain_expired`]返回的迭代器, 每次迭代取出一个到期的定时器
+pub struct DrainExpired<'a, Q, T> {
+    queue: &'a mut Q,
+    now: u64,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, Q, T> DrainExpired<'a, Q, T> {
+    pub(crate) fn new(queue: &'a mut Q, now: u64) -> Self {
+        Self {
+            queue,
+            now,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, Q: TimerQueue<T>, T: Timer> Iterator for DrainExpired<'a, Q, T> {
+    type Item = (u64, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.queue.poll_expired(self.now)
+    }
+}
+
+macro_rules! impl_timer_queue {
+    ($name:ident) => {
+        impl<T: Timer> TimerQueue<T> for $name<T> {
+            fn len(&self) -> usize {
+                $name::len(self)
+            }
+
+            fn is_empty(&self) -> bool {
+                $name::is_empty(self)
+            }
+
+            fn clear(&mut self) {
+                $name::clear(self)
+            }
+
+            fn now(&self) -> u64 {
+                $name::now(self)
+            }
+
+            fn add_timer_at(&mut self, val: T, at: u64) -> u64 {
+                $name::add_timer_at(self, val, at)
+            }
+
+            fn add_timer_by_id_at(&mut self, timer_id: u64, val: T, at: u64) {
+                $name::add_timer_by_id_at(self, timer_id, val, at)
+            }
+
+            fn del_timer(&mut self, timer_id: u64) -> Option<T> {
+                $name::del_timer(self, timer_id)
+            }
+
+            fn get_timer(&self, timer_id: &u64) -> Option<&T> {
+                $name::get_timer(self, timer_id)
+            }
+
+            fn get_mut_timer(&mut self, timer_id: &u64) -> Option<&mut T> {
+                $name::get_mut_timer(self, timer_id)
+            }
+
+            fn reset_timer_at(&mut self, timer_id: u64, at: u64) -> bool {
+                $name::reset_timer_at(self, timer_id, at)
+            }
+
+            fn postpone(&mut self, timer_id: u64, delta: u64) -> bool {
+                $name::postpone(self, timer_id, delta)
+            }
+
+            fn tick_first(&self) -> Option<u64> {
+                $name::tick_first(self)
+            }
+
+            fn next_delay(&self) -> Option<u64> {
+                $name::next_delay(self)
+            }
+
+            fn advance_to(&mut self, now: u64) -> Option<Vec<(u64, T)>> {
+                $name::advance_to(self, now)
+            }
+
+            fn update_deltatime(&mut self, delta: u64) -> Option<Vec<(u64, T)>> {
+                $name::update_deltatime(self, delta)
+            }
+
+            fn poll_expired(&mut self, now: u64) -> Option<(u64, T)> {
+                $name::poll_expired(self, now)
+            }
+
+            fn drain_expired(&mut self, now: u64) -> DrainExpired<'_, Self, T> {
+                $name::drain_expired(self, now)
+            }
+
+            fn export_timers(&self) -> Vec<PendingTimer<T>>
+            where
+                T: Clone,
+            {
+                $name::export_timers(self)
+            }
+
+            fn restore_timers<I>(&mut self, timers: I) -> Result<(), u64>
+            where
+                T: Clone,
+                I: IntoIterator<Item = PendingTimer<T>>,
+            {
+                $name::restore_timers(self, timers)
+            }
+        }
+    };
+}
+
+impl_timer_queue!(TimerWheel);
+impl_timer_queue!(TimerRBTree);
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::TimerQueue;
+    use crate::{TimerRBTree, TimerWheel};
+
+    #[test]
+    fn test_same_time_model() {
+        let mut rng = rand::rng();
+        let mut wheel = TimerWheel::builder().levels([16, 8, 4]).build().unwrap();
+        let mut tree = TimerRBTree::new();
+        for _ in 0..3000 {
+            let op = rng.random_range(0..6);
+            let (a, b) = (rng.random_range(1..700u64), rng.random_range(0..5u64));
+            assert_eq!(apply(&mut wheel, op, a, b), apply(&mut tree, op, a, b));
+        }
+    }
+
+    /// 执行一个操作, 返回当前时间, 定时器数量及到期的定时器
+    fn apply<Q: TimerQueue<u64>>(queue: &mut Q, op: u32, a: u64, b: u64) -> Vec<(u64, u64)> {
+        let mut fired = match op {
+            0 => {
+                queue.add_timer(a);
+                vec![]
+            }
+            1 => {
+                queue.add_timer_at(a, queue.now() + a / 2 + 1);
+                vec![]
+            }
+            2 => {
+                queue.reset_timer(b + 1, a);
+                vec![]
+            }
+            3 => queue.update_deltatime(b).unwrap_or_default(),
+            // 传入较早的时间时不回退
+            4 => queue.advance_to(queue.now().saturating_sub(b)).unwrap_or_default(),
+            _ => queue.advance_to(queue.now() + a / 10).unwrap_or_default(),
+        };
+        fired.sort_unstable();
+        fired.push((queue.now(), queue.len() as u64));
+        fired.push((queue.tick_first().unwrap_or(0), queue.next_delay().unwrap_or(0)));
+        fired
+    }
+}
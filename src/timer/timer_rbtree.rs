@@ -5,7 +5,7 @@ use std::cmp::Ordering;
 use std::u64;
 use std::vec;
 
-use super::Timer;
+use super::{timer_queue::restore_next_id, DrainExpired, PendingTimer, Repeat, Timer};
 
 #[derive(PartialEq, Eq)]
 struct TreeKey(u64, u64);
@@ -67,6 +67,9 @@ pub struct TimerRBTree<T: Timer> {
 
     map: HashMap<u64, u64>,
 
+    /// 周期定时器的重复信息
+    repeats: HashMap<u64, Repeat<T>>,
+
     /// 当时记录的时序
     cur_step: u64,
 
@@ -81,6 +84,7 @@ impl<T: Timer> TimerRBTree<T> {
         Self {
             tree: RBTree::new(),
             map: HashMap::new(),
+            repeats: HashMap::new(),
             cur_step: 0,
             next_timer_id: 1,
             max_timer_id: u64::MAX,
@@ -129,6 +133,7 @@ impl<T: Timer> TimerRBTree<T> {
     pub fn clear(&mut self) {
         self.tree.clear();
         self.map.clear();
+        self.repeats.clear();
         self.cur_step = 0;
         self.next_timer_id = 1;
     }
@@ -158,7 +163,7 @@ impl<T: Timer> TimerRBTree<T> {
         timer_id
     }
 
-    /// 添加定时器元素
+    /// 添加定时器元素, 在当前时间的[`Timer::when`]之后触发
     /// # Examples
     ///
     /// ```
@@ -174,11 +179,171 @@ impl<T: Timer> TimerRBTree<T> {
         timer_id
     }
 
-    pub fn add_timer_by_id(&mut self, timer_id: u64, val: T) {
-        let when = val.when();
+    /// 以指定的id添加在当前时间的[`Timer::when`]之后触发的定时器, 该id已存在时替换原有的定时器
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::TimerRBTree;
+    /// fn main() {
+    ///     let mut timer = TimerRBTree::new();
+    ///     timer.add_timer_by_id(5, 10u64);
+    ///     timer.add_timer_by_id(5, 20u64);
+    ///     assert_eq!(timer.len(), 1);
+    ///     assert_eq!(timer.get_timer(&5), Some(&20));
+    /// }
+    /// ```
+    pub fn add_timer_by_id(&mut self, timer_id: u64, mut val: T) {
+        self.del_timer(timer_id);
+        let when = self.cur_step.saturating_add(val.when_mut());
+        self.insert(timer_id, when, val);
+    }
+
+    /// 添加在时间线上`at`时触发的定时器, 不使用值的[`Timer::when`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::TimerRBTree;
+    /// fn main() {
+    ///     let mut timer = TimerRBTree::new();
+    ///     let t = timer.add_timer_at(0u64, 25);
+    ///     assert_eq!(timer.tick_first(), Some(25));
+    ///     assert_eq!(timer.advance_to(25).unwrap(), vec![(t, 0)]);
+    /// }
+    /// ```
+    pub fn add_timer_at(&mut self, val: T, at: u64) -> u64 {
+        let timer_id = self.get_next_timerid();
+        self.add_timer_by_id_at(timer_id, val, at);
+        timer_id
+    }
+
+    /// 以指定的id添加在时间线上`at`时触发的定时器, 该id已存在时替换原有的定时器
+    pub fn add_timer_by_id_at(&mut self, timer_id: u64, val: T, at: u64) {
+        self.del_timer(timer_id);
+        self.insert(timer_id, at, val);
+    }
+
+    fn insert(&mut self, timer_id: u64, when: u64, val: T) {
         self.tree.insert(TreeKey(when, timer_id), val);
         self.map.insert(timer_id, when);
     }
+    /// 添加周期定时器, 每隔`period`触发一次, 触发后以同一个id自动重新加入,
+    /// 通过`del_timer`可取消整个周期, 首次触发在当前时序的`period`之后
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::TimerRBTree;
+    /// fn main() {
+    ///     let mut timer = TimerRBTree::new();
+    ///     let t = timer.add_interval(7u64, 10);
+    ///     assert_eq!(timer.update_deltatime(10).unwrap(), vec![(t, 7)]);
+    ///     assert_eq!(timer.tick_first(), Some(20));
+    ///     assert_eq!(timer.update_deltatime(10).unwrap(), vec![(t, 7)]);
+    ///     assert_eq!(timer.del_timer(t), Some(7));
+    ///     assert!(timer.is_empty());
+    /// }
+    /// ```
+    pub fn add_interval(&mut self, val: T, period: u64) -> u64
+    where
+        T: Clone,
+    {
+        self.insert_repeat(val, Repeat::new(period, None))
+    }
+
+    /// 添加最多触发`times`次的周期定时器, 最后一次触发后自动移除
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::TimerRBTree;
+    /// fn main() {
+    ///     let mut timer = TimerRBTree::new();
+    ///     let t = timer.add_interval_times(1, 5, 2);
+    ///     assert_eq!(timer.update_deltatime(20).unwrap(), vec![(t, 1)]);
+    ///     assert_eq!(timer.update_deltatime(1).unwrap(), vec![(t, 1)]);
+    ///     assert!(timer.is_empty());
+    /// }
+    /// ```
+    pub fn add_interval_times(&mut self, val: T, period: u64, times: u64) -> u64
+    where
+        T: Clone,
+    {
+        self.insert_repeat(val, Repeat::new(period, Some(times)))
+    }
+
+    /// 添加自动重复的定时器, 每次触发后以值的[`Timer::when_mut`]重新计算下次的触发时间并以同一个id重新加入,
+    /// 适用于触发间隔不固定的定时器, 如[`super::CronTimer`], 通过`del_timer`可取消
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "sim")]
+    /// use algorithm::{CronTimer, SimClock, TimerRBTree};
+    /// # #[cfg(feature = "sim")]
+    /// fn main() {
+    ///     // 2024-01-01 00:00:00 UTC
+    ///     let mut clock = SimClock::new(1_704_067_200_000);
+    ///     let mut timer = TimerRBTree::new();
+    ///     let cron = CronTimer::new("daily", "0 3 * * *".parse().unwrap());
+    ///     let t = timer.add_repeat(cron);
+    ///     let hour = 3600 * 1000;
+    ///     assert_eq!(timer.next_delay(), Some(3 * hour));
+    ///     clock.advance(3 * hour);
+    ///     let fired = timer.update_deltatime(3 * hour).unwrap();
+    ///     assert_eq!(fired.iter().map(|(id, v)| (*id, v.val)).collect::<Vec<_>>(), vec![(t, "daily")]);
+    ///     assert_eq!(timer.next_delay(), Some(24 * hour));
+    /// }
+    /// # #[cfg(not(feature = "sim"))]
+    /// # fn main() {}
+    /// ```
+    pub fn add_repeat(&mut self, val: T) -> u64
+    where
+        T: Clone,
+    {
+        self.insert_repeat(val, Repeat::by_when())
+    }
+
+    fn insert_repeat(&mut self, mut val: T, repeat: Repeat<T>) -> u64 {
+        let timer_id = self.get_next_timerid();
+        let when = match repeat.period {
+            Some(period) => period,
+            None => val.when_mut(),
+        };
+        let when = self.cur_step.saturating_add(when);
+        self.tree.insert(TreeKey(when, timer_id), val);
+        self.map.insert(timer_id, when);
+        self.repeats.insert(timer_id, repeat);
+        timer_id
+    }
+
+    /// 取出最小的一个定时器, 周期定时器将复制一份返回并重新加入
+    fn pop_first(&mut self) -> Option<(u64, T)> {
+        let (key, mut val) = self.tree.pop_first()?;
+        let timer_id = key.1;
+        let again = self.repeats.get_mut(&timer_id).is_some_and(|r| r.fire());
+        match self.repeats.get(&timer_id).filter(|_| again) {
+            Some(repeat) => {
+                // 落后太多时不追赶触发, 每次更新最多触发一次
+                let ret = (repeat.clone)(&val);
+                let when = match repeat.period {
+                    Some(period) => key.0.saturating_add(period),
+                    None => self.cur_step.saturating_add(val.when_mut()),
+                };
+                let when = when.max(self.cur_step + 1);
+                self.tree.insert(TreeKey(when, timer_id), val);
+                self.map.insert(timer_id, when);
+                Some((timer_id, ret))
+            }
+            _ => {
+                self.repeats.remove(&timer_id);
+                self.map.remove(&timer_id);
+                Some((timer_id, val))
+            }
+        }
+    }
+
     /// 删除指定的定时器，时间复杂度为O(logn)，
     ///
     /// # Examples
@@ -192,6 +357,7 @@ impl<T: Timer> TimerRBTree<T> {
     ///     assert_eq!(timer.len(), 0);
     /// }
     pub fn del_timer(&mut self, timer_id: u64) -> Option<T> {
+        self.repeats.remove(&timer_id);
         if let Some(when) = self.map.remove(&timer_id) {
             let tree = TreeKey(when, timer_id);
             self.tree.remove(&tree).map(|e| e)
@@ -242,6 +408,66 @@ impl<T: Timer> TimerRBTree<T> {
         }
     }
 
+    /// 将定时器的触发时间重新设置为当前时间之后的`when`, 保留原有的id及值, 时间复杂度为O(log(n))
+    /// 返回false表示该定时器不存在
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::TimerRBTree;
+    /// fn main() {
+    ///     let mut timer = TimerRBTree::new();
+    ///     let t = timer.add_timer(10);
+    ///     assert!(timer.reset_timer(t, 30));
+    ///     assert_eq!(timer.tick_first(), Some(30));
+    ///     assert_eq!(timer.update_deltatime(10), None);
+    ///     assert_eq!(timer.update_deltatime(20).unwrap(), vec![(t, 10)]);
+    ///     assert!(!timer.reset_timer(t, 30));
+    /// }
+    /// ```
+    pub fn reset_timer(&mut self, timer_id: u64, when: u64) -> bool {
+        self.reset_timer_at(timer_id, self.cur_step.saturating_add(when))
+    }
+
+    /// 将定时器的触发时间重新设置为时间线上的`at`, 保留原有的id及值
+    /// 返回false表示该定时器不存在
+    pub fn reset_timer_at(&mut self, timer_id: u64, at: u64) -> bool {
+        let old = match self.map.get_mut(&timer_id) {
+            Some(old) => std::mem::replace(old, at),
+            None => return false,
+        };
+        if let Some(val) = self.tree.remove(&TreeKey(old, timer_id)) {
+            self.tree.insert(TreeKey(at, timer_id), val);
+        }
+        true
+    }
+
+    /// 将定时器的触发时间延后`delta`, 保留原有的id及值
+    /// 返回false表示该定时器不存在
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::TimerRBTree;
+    /// fn main() {
+    ///     let mut timer = TimerRBTree::new();
+    ///     let t = timer.add_timer(10);
+    ///     assert!(timer.postpone(t, 5));
+    ///     assert_eq!(timer.tick_first(), Some(15));
+    /// }
+    /// ```
+    pub fn postpone(&mut self, timer_id: u64, delta: u64) -> bool {
+        match self.map.get(&timer_id) {
+            Some(when) => self.reset_timer_at(timer_id, when.saturating_add(delta)),
+            None => false,
+        }
+    }
+
+    /// 当前在时间线上的时间, 即最后一次递进到的时间
+    pub fn now(&self) -> u64 {
+        self.cur_step
+    }
+
     /// 取出时间轴最小的一个值
     pub fn tick_first(&self) -> Option<u64> {
         self.tree
@@ -250,12 +476,111 @@ impl<T: Timer> TimerRBTree<T> {
             .unwrap_or(None)
     }
 
+    /// 距离最早到期的定时器触发还需递进的时间, 为空时返回None
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::TimerRBTree;
+    /// fn main() {
+    ///     let mut timer = TimerRBTree::new();
+    ///     assert_eq!(timer.next_delay(), None);
+    ///     timer.add_timer(30u64);
+    ///     timer.update_deltatime(10);
+    ///     assert_eq!(timer.next_delay(), Some(20));
+    /// }
+    /// ```
+    pub fn next_delay(&self) -> Option<u64> {
+        self.tick_first()
+            .map(|when| when.saturating_sub(self.cur_step))
+    }
+
+    /// 导出所有待触发的定时器, 按剩余时间排序, 可在重启后通过[`TimerRBTree::restore_timers`]恢复,
+    /// 周期定时器同时导出重复周期及剩余的触发次数
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::TimerRBTree;
+    /// fn main() {
+    ///     let mut timer = TimerRBTree::new();
+    ///     let a = timer.add_timer(30u64);
+    ///     let b = timer.add_timer(50u64);
+    ///     timer.update_deltatime(20);
+    ///     let pending = timer.export_timers();
+    ///     let remain: Vec<_> = pending.iter().map(|p| (p.timer_id, p.remain)).collect();
+    ///     assert_eq!(remain, vec![(a, 10), (b, 30)]);
+    ///
+    ///     // 重启后的时间线从0开始, 以剩余时间重新加入
+    ///     let mut restored = TimerRBTree::new();
+    ///     restored.restore_timers(pending).unwrap();
+    ///     assert_eq!(restored.tick_first(), Some(10));
+    ///     assert_eq!(restored.update_deltatime(30).unwrap(), vec![(a, 30), (b, 50)]);
+    /// }
+    /// ```
+    pub fn export_timers(&self) -> Vec<PendingTimer<T>>
+    where
+        T: Clone,
+    {
+        self.tree
+            .iter()
+            .map(|(key, val)| PendingTimer {
+                timer_id: key.1,
+                remain: key.0.saturating_sub(self.cur_step),
+                val: val.clone(),
+                repeat: self.repeats.get(&key.1).map(Repeat::export),
+            })
+            .collect()
+    }
+
+    /// 恢复导出的定时器, 保留原有的id, 触发时间为当前时序加上剩余时间, 该id已存在时替换原有的定时器,
+    /// 周期定时器恢复后以导出的周期继续重复
+    ///
+    /// 所有的id须在`1..=max_timer_id`的范围内, 否则不做任何修改并返回超出范围的id
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::TimerRBTree;
+    /// fn main() {
+    ///     let mut timer = TimerRBTree::new();
+    ///     let t = timer.add_interval(7u64, 10);
+    ///     timer.update_deltatime(15);
+    ///     let pending = timer.export_timers();
+    ///     assert_eq!(pending[0].remain, 5);
+    ///     assert_eq!(pending[0].repeat.unwrap().period, Some(10));
+    ///
+    ///     let mut restored = TimerRBTree::new();
+    ///     restored.restore_timers(pending).unwrap();
+    ///     assert_eq!(restored.update_deltatime(5).unwrap(), vec![(t, 7)]);
+    ///     assert_eq!(restored.update_deltatime(10).unwrap(), vec![(t, 7)]);
+    ///     assert_eq!(restored.len(), 1);
+    /// }
+    /// ```
+    pub fn restore_timers<I>(&mut self, timers: I) -> Result<(), u64>
+    where
+        T: Clone,
+        I: IntoIterator<Item = PendingTimer<T>>,
+    {
+        let timers: Vec<_> = timers.into_iter().collect();
+        self.next_timer_id = restore_next_id(&timers, self.next_timer_id, self.max_timer_id)?;
+        for p in timers {
+            self.del_timer(p.timer_id);
+            let when = self.cur_step.saturating_add(p.remain);
+            if let Some(repeat) = p.repeat {
+                self.repeats.insert(p.timer_id, Repeat::restore(repeat));
+            }
+            self.insert(p.timer_id, when, p.val);
+        }
+        Ok(())
+    }
+
     /// 判断到指定时间是否有小于该指定值的实例
     pub fn tick_time(&mut self, tm: u64) -> Option<T> {
         if tm < self.tick_first().unwrap_or(tm + 1) {
             return None;
         }
-        self.tree.pop_first().map(|(_, e)| e)
+        self.pop_first().map(|(_, e)| e)
     }
 
     /// 计时器轮的递进时间
@@ -271,20 +596,67 @@ impl<T: Timer> TimerRBTree<T> {
     ///     assert_eq!(val.iter().map(|(_, v)| *v).collect::<Vec<usize>>(), vec![30]);
     /// }
     pub fn update_now(&mut self, now: u64) -> Option<Vec<(u64, T)>> {
-        self.cur_step = now;
-        let mut result = vec![];
-        loop {
-            if let Some(val) = self.tick_first() {
-                if self.cur_step < val {
-                    break;
-                }
-                result.push(self.tree.pop_first().map(|(k, e)| (k.1, e)).unwrap());
-            } else {
-                break;
-            }
+        let first = self.poll_expired(now)?;
+        let mut result = vec![first];
+        while let Some(val) = self.poll_expired(now) {
+            result.push(val);
         }
         Some(result)
     }
+
+    /// 递进到时间线上的`now`, 与[`TimerRBTree::update_now`]相同, 与[`super::TimerWheel::advance_to`]对应
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::TimerRBTree;
+    /// fn main() {
+    ///     let mut timer = TimerRBTree::new();
+    ///     timer.add_timer(30);
+    ///     assert_eq!(timer.advance_to(20), None);
+    ///     assert_eq!(timer.advance_to(30).unwrap(), vec![(1, 30)]);
+    ///     assert_eq!(timer.now(), 30);
+    /// }
+    /// ```
+    pub fn advance_to(&mut self, now: u64) -> Option<Vec<(u64, T)>> {
+        self.update_now(now)
+    }
+
+    /// 递进到时间线上的`now`并取出一个到期的定时器, 没有到期的定时器时返回None, `now`早于当前时间时不回退
+    ///
+    /// 每次调用只取出一个, 不分配内存, 调用者可随时停止以限制每次的工作量,
+    /// 未取出的到期定时器保留在树中, 下次调用时继续取出
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::TimerRBTree;
+    /// fn main() {
+    ///     let mut timer = TimerRBTree::new();
+    ///     for v in [10u64, 20, 30, 40] {
+    ///         timer.add_timer(v);
+    ///     }
+    ///     assert_eq!(timer.poll_expired(5), None);
+    ///     assert_eq!(timer.poll_expired(30), Some((1, 10)));
+    ///     assert_eq!(timer.poll_expired(30), Some((2, 20)));
+    ///     let rest: Vec<_> = timer.drain_expired(50).collect();
+    ///     assert_eq!(rest, vec![(3, 30), (4, 40)]);
+    /// }
+    /// ```
+    pub fn poll_expired(&mut self, now: u64) -> Option<(u64, T)> {
+        // 时间线只前进, 传入较早的时间时不回退
+        self.cur_step = self.cur_step.max(now);
+        if self.tick_first()? > self.cur_step {
+            return None;
+        }
+        self.pop_first()
+    }
+
+    /// 递进到指定的时间, 返回逐个取出到期定时器的迭代器, 见[`TimerRBTree::poll_expired`]
+    pub fn drain_expired(&mut self, now: u64) -> DrainExpired<'_, Self, T> {
+        DrainExpired::new(self, now)
+    }
+
     /// 计时器轮的递进时间
     ///
     /// # Examples
@@ -327,6 +699,7 @@ impl<T: Timer> TimerRBTree<T> {
         self.update_now_with_callback(self.cur_step.wrapping_add(delta), f)
     }
 
+    /// 递进到时间线上的`now`, 到期的定时器交由回调处理, 回调返回`Some`时以返回的id重新添加
     pub fn update_now_with_callback<F>(&mut self, now: u64, f: &mut F)
     where
         F: FnMut(&mut Self, u64, T) -> Option<(u64, T)>,
@@ -343,4 +716,12 @@ impl<T: Timer> TimerRBTree<T> {
             }
         }
     }
+
+    /// 与[`TimerRBTree::update_now_with_callback`]相同, 与[`super::TimerWheel::advance_to_with_callback`]对应
+    pub fn advance_to_with_callback<F>(&mut self, now: u64, f: &mut F)
+    where
+        F: FnMut(&mut Self, u64, T) -> Option<(u64, T)>,
+    {
+        self.update_now_with_callback(now, f)
+    }
 }
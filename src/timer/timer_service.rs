@@ -0,0 +1,210 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use super::{Timer, TimerQueue};
+
+enum Command<T> {
+    Add(u64, T),
+    Del(u64),
+    Reset(u64, u64),
+    Stop,
+}
+
+/// 定时器服务的句柄, 可clone后在任意线程添加或取消定时器
+pub struct TimerHandle<T> {
+    sender: Sender<Command<T>>,
+    next_timer_id: Arc<AtomicU64>,
+}
+
+impl<T> Clone for TimerHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            next_timer_id: self.next_timer_id.clone(),
+        }
+    }
+}
+
+impl<T: Timer + Send + 'static> TimerHandle<T> {
+    /// 添加定时器元素, 返回定时器id, 服务已停止时该定时器不会触发
+    pub fn add_timer(&self, val: T) -> u64 {
+        let timer_id = self.next_timer_id.fetch_add(1, Ordering::Relaxed);
+        let _ = self.sender.send(Command::Add(timer_id, val));
+        timer_id
+    }
+
+    /// 取消指定的定时器
+    pub fn del_timer(&self, timer_id: u64) {
+        let _ = self.sender.send(Command::Del(timer_id));
+    }
+
+    /// 将定时器的触发时间重新设置为服务线程处理该请求时之后的`when`, 保留原有的id及值
+    pub fn reset_timer(&self, timer_id: u64, when: u64) {
+        let _ = self.sender.send(Command::Reset(timer_id, when));
+    }
+}
+
+/// 在独立线程中以真实时间驱动[`TimerQueue`]的定时器服务, 如[`super::TimerWheel`]或[`super::TimerRBTree`]
+///
+/// 服务线程以启动后经过的`unit`数作为时间线上的时间, 通过[`TimerQueue::advance_to_with_callback`]递进定时器,
+/// 定时器的时间单位即为`unit`, 到期的定时器交由回调处理或发送到通道中, 回调返回`Some`时以返回的id重新添加定时器
+///
+/// 服务线程只在收到命令或最早的定时器到期时唤醒, 没有定时器时不会空转
+///
+/// 两种实现使用相同的时间模型, 定时器的[`Timer::when`]均为相对添加时的延时
+///
+/// # Examples
+///
+/// ```
+/// use algorithm::{TimerService, TimerWheel};
+/// use std::time::Duration;
+/// fn main() {
+///     let mut wheel = TimerWheel::new();
+///     wheel.append_timer_wheel(1000, "MillisWheel");
+///     let (service, receiver) = TimerService::with_channel(wheel, Duration::from_millis(1));
+///     let handle = service.handle();
+///     let t = handle.add_timer(10u64);
+///     let cancel = handle.add_timer(20u64);
+///     handle.del_timer(cancel);
+///     assert_eq!(receiver.recv().unwrap(), (t, 10));
+///     assert!(receiver.recv_timeout(Duration::from_millis(50)).is_err());
+///     service.stop();
+/// }
+/// ```
+pub struct TimerService<T: Timer + Send + 'static> {
+    handle: TimerHandle<T>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<T: Timer + Send + 'static> TimerService<T> {
+    /// 创建定时器服务, 到期的定时器由`callback`在服务线程中处理
+    ///
+    /// ```
+    /// use algorithm::{TimerRBTree, TimerService};
+    /// use std::sync::mpsc;
+    /// use std::time::Duration;
+    /// fn main() {
+    ///     let (sender, receiver) = mpsc::channel();
+    ///     let service = TimerService::new(TimerRBTree::new(), Duration::from_millis(1), move |id, v: u64| {
+    ///         sender.send(v).unwrap();
+    ///         // 重复触发三次
+    ///         if v < 30 {
+    ///             Some((id, v + 10))
+    ///         } else {
+    ///             None
+    ///         }
+    ///     });
+    ///     service.handle().add_timer(10);
+    ///     let vals: Vec<u64> = receiver.iter().take(3).collect();
+    ///     assert_eq!(vals, vec![10, 20, 30]);
+    /// }
+    /// ```
+    pub fn new<Q, F>(timer: Q, unit: Duration, mut callback: F) -> Self
+    where
+        Q: TimerQueue<T> + Send + 'static,
+        F: FnMut(u64, T) -> Option<(u64, T)> + Send + 'static,
+    {
+        debug_assert!(timer.is_empty(), "定时器的id由服务分配, 必须传入空的定时器");
+        debug_assert!(!unit.is_zero());
+        let (sender, receiver) = mpsc::channel();
+        let thread = thread::spawn(move || {
+            Self::run(timer, unit, receiver, &mut callback);
+        });
+        Self {
+            handle: TimerHandle {
+                sender,
+                next_timer_id: Arc::new(AtomicU64::new(1)),
+            },
+            thread: Some(thread),
+        }
+    }
+
+    /// 创建定时器服务, 到期的定时器`(id, val)`发送到返回的通道中
+    pub fn with_channel<Q>(timer: Q, unit: Duration) -> (Self, Receiver<(u64, T)>)
+    where
+        Q: TimerQueue<T> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let service = Self::new(timer, unit, move |id, val| {
+            let _ = sender.send((id, val));
+            None
+        });
+        (service, receiver)
+    }
+
+    /// 获取可clone的句柄
+    pub fn handle(&self) -> TimerHandle<T> {
+        self.handle.clone()
+    }
+
+    /// 停止服务并等待服务线程退出, 未触发的定时器将被丢弃
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            let _ = self.handle.sender.send(Command::Stop);
+            let _ = thread.join();
+        }
+    }
+
+    fn run<Q, F>(mut timer: Q, unit: Duration, receiver: Receiver<Command<T>>, callback: &mut F)
+    where
+        Q: TimerQueue<T>,
+        F: FnMut(u64, T) -> Option<(u64, T)>,
+    {
+        let start = Instant::now();
+        let base = timer.now();
+        loop {
+            // 没有定时器时阻塞等待命令, 否则最多等到最早的定时器到期, 至少等待到下一个时间单位
+            let received = match timer.next_delay() {
+                None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                Some(delay) => {
+                    let target = (timer.now() - base).saturating_add(delay.max(1));
+                    let deadline = (target as u128).saturating_mul(unit.as_nanos());
+                    let deadline = Duration::from_nanos(deadline.min(u64::MAX as u128) as u64);
+                    receiver.recv_timeout(deadline.saturating_sub(start.elapsed()))
+                }
+            };
+            let mut command = match received {
+                Ok(command) => Some(command),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => return,
+            };
+
+            // 先递进到当前时间, 新添加的定时器以处理命令时的时间计算延时
+            let now = base + (start.elapsed().as_nanos() / unit.as_nanos()) as u64;
+            if now > timer.now() {
+                timer.advance_to_with_callback(now, &mut |_, id, val| callback(id, val));
+            }
+
+            while let Some(c) = command {
+                match c {
+                    Command::Add(timer_id, val) => timer.add_timer_by_id(timer_id, val),
+                    Command::Del(timer_id) => {
+                        timer.del_timer(timer_id);
+                    }
+                    Command::Reset(timer_id, when) => {
+                        timer.reset_timer(timer_id, when);
+                    }
+                    Command::Stop => return,
+                }
+                command = receiver.try_recv().ok();
+            }
+        }
+    }
+}
+
+impl<T: Timer + Send + 'static> Drop for TimerService<T> {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
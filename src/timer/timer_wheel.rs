@@ -1,179 +1,240 @@
 use std::{
+    error::Error,
     fmt::{self, Display},
-    ptr, u64,
+    marker::PhantomData,
+    time::Duration,
 };
 
-use super::Timer;
+use crate::HashMap;
+
+use super::{timer_queue::restore_next_id, DrainExpired, PendingTimer, Repeat, Timer};
+
+/// 超出最大轮范围的定时器所在的层级
+const OVERFLOW: usize = usize::MAX;
+/// 已到期等待取出的定时器所在的层级
+const READY: usize = usize::MAX - 1;
+
+/// 构建器生成的各层时轮名字
+const LEVEL_NAMES: [&str; 8] = [
+    "Wheel0", "Wheel1", "Wheel2", "Wheel3", "Wheel4", "Wheel5", "Wheel6", "Wheel7",
+];
 
 struct Entry<T: Timer> {
     val: T,
-    when: u64,
     id: u64,
+    /// 到期的时序, 以刻度为单位的绝对值
+    deadline: u64,
+    /// 所在的轮及槽位, 删除时可直接定位
+    level: usize,
+    slot: usize,
+    /// 槽位中的前后节点, 组成侵入式的双向链表
+    prev: Option<u64>,
+    next: Option<u64>,
+    /// 周期定时器的重复信息
+    repeat: Option<Repeat<T>>,
+}
+
+impl<T: Timer> Entry<T> {
+    fn new(id: u64, val: T) -> Self {
+        Self {
+            val,
+            id,
+            deadline: 0,
+            level: OVERFLOW,
+            slot: 0,
+            prev: None,
+            next: None,
+            repeat: None,
+        }
+    }
+}
+
+/// 槽位的链表头尾
+#[derive(Clone, Copy, Default)]
+struct Slot {
+    head: Option<u64>,
+    tail: Option<u64>,
+}
+
+impl Slot {
+    fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    /// 将节点链接到槽位的尾部
+    fn push<T: Timer>(&mut self, entries: &mut HashMap<u64, Entry<T>>, timer_id: u64) {
+        let tail = self.tail;
+        {
+            let entry = entries.get_mut(&timer_id).expect("timer entry");
+            entry.prev = tail;
+            entry.next = None;
+        }
+        match tail {
+            Some(tail) => entries.get_mut(&tail).expect("timer link").next = Some(timer_id),
+            None => self.head = Some(timer_id),
+        }
+        self.tail = Some(timer_id);
+    }
+
+    /// 将节点从槽位中摘除, 时间复杂度为O(1)
+    fn unlink<T: Timer>(&mut self, entries: &mut HashMap<u64, Entry<T>>, entry: &Entry<T>) {
+        match entry.prev {
+            Some(prev) => entries.get_mut(&prev).expect("timer link").next = entry.next,
+            None => self.head = entry.next,
+        }
+        match entry.next {
+            Some(next) => entries.get_mut(&next).expect("timer link").prev = entry.prev,
+            None => self.tail = entry.prev,
+        }
+    }
+
+    /// 取出槽位头部的节点
+    fn pop_front<T: Timer>(&mut self, entries: &mut HashMap<u64, Entry<T>>) -> Option<u64> {
+        let timer_id = self.head?;
+        self.head = entries[&timer_id].next;
+        match self.head {
+            Some(next) => entries.get_mut(&next).expect("timer link").prev = None,
+            None => self.tail = None,
+        }
+        Some(timer_id)
+    }
 }
+
 /// 单轮结构
-pub struct OneTimerWheel<T: Timer> {
-    /// 当时指针指向的位置，如秒针指向3点钟方向
-    index: u64,
-    /// 当前结构的容量，比如60s可能有30个槽,每个都是2秒
-    capation: u64,
+struct OneTimerWheel {
     /// 当前槽的个数
     num: u64,
-    /// 当前结构步长，如分钟就表示60s的
+    /// 每个槽位的刻度数，如分钟轮每个槽为60个秒刻度
     step: u64,
-    /// 修正步长，当前的步长*基础步长
-    fix_step: u64,
-    /// 当前槽位容纳的元素
-    slots: Vec<Vec<Entry<T>>>,
-    /// 当前轮结构的父轮，如当前是分的，那父轮为时轮
-    parent: *mut OneTimerWheel<T>,
-    /// 当前轮结构的子轮，如当前是分的，那父轮为秒轮
-    child: *mut OneTimerWheel<T>,
+    /// 当前槽位容纳的元素链表
+    slots: Vec<Slot>,
     /// 当前轮的名字，辅助定位
     name: &'static str,
 }
 
-impl<T: Timer> OneTimerWheel<T> {
-    pub fn new(num: u64, step: u64, one_step: u64, name: &'static str) -> Self {
-        let mut slots = vec![];
-        for _ in 0..num {
-            slots.push(Vec::new());
-        }
+impl OneTimerWheel {
+    fn new(num: u64, step: u64, name: &'static str) -> Self {
         Self {
-            index: 0,
-            capation: num * step,
             num,
             step,
-            fix_step: one_step * step,
-            slots,
-            parent: ptr::null_mut(),
-            child: ptr::null_mut(),
+            slots: vec![Slot::default(); num as usize],
             name,
         }
     }
 
-    pub fn clear(&mut self) {
-        for idx in 0..self.num as usize {
-            self.slots[idx].clear();
+    fn clear(&mut self) {
+        for slot in self.slots.iter_mut() {
+            *slot = Slot::default();
         }
     }
 
-    pub fn append(&mut self, next: *mut OneTimerWheel<T>) {
-        if self.child.is_null() {
-            unsafe {
-                (*next).parent = self;
-                self.child = next;
-            }
-        } else {
-            unsafe {
-                (*self.child).append(next);
-            }
-        }
+    /// 该轮能否容纳到期时序, 即到期时序与当前时序相差的槽位数不超过该轮的槽位数
+    fn contains(&self, deadline: u64, now: u64) -> bool {
+        deadline / self.step - now / self.step < self.num
     }
 
-    fn add_timer(&mut self, entry: Entry<T>) {
-        let offset = entry.when;
-        self.add_timer_with_offset(entry, offset);
+    fn slot_index(&self, deadline: u64) -> usize {
+        ((deadline / self.step) % self.num) as usize
     }
 
-    fn del_timer(&mut self, timer_id: u64) -> Option<T> {
-        for i in 0..self.num as usize {
-            let mut found_idx = None;
-            for (idx, val) in self.slots[i].iter().enumerate() {
-                if val.id == timer_id {
-                    found_idx = Some(idx);
-                    break;
-                }
-            }
-            if let Some(idx) = found_idx {
-                return Some(self.slots[i].remove(idx).val);
-            }
-        }
-        None
+    /// 当前时序之后第一个非空槽位的起始时序
+    fn next_event(&self, now: u64) -> Option<u64> {
+        let cur = now / self.step;
+        (1..=self.num)
+            .map(|i| cur + i)
+            .find(|abs| !self.slots[(abs % self.num) as usize].is_empty())
+            .map(|abs| abs.saturating_mul(self.step))
     }
+}
 
-    fn get_timer(&self, timer_id: &u64) -> Option<&T> {
-        for i in 0..self.num as usize {
-            for val in self.slots[i].iter() {
-                if &val.id == timer_id {
-                    return Some(&val.val);
-                }
-            }
+/// 构建时轮时配置不合法的错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerWheelError {
+    /// 刻度必须为不小于1毫秒的整毫秒数
+    InvalidTick,
+    /// 至少需要一层时轮
+    EmptyLevels,
+    /// 第n层的槽位数为0
+    ZeroSlots(usize),
+    /// 时轮总的刻度数超出u64的范围
+    Overflow,
+}
+
+impl Display for TimerWheelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimerWheelError::InvalidTick => f.write_str("tick must be a whole number of milliseconds"),
+            TimerWheelError::EmptyLevels => f.write_str("timer wheel needs at least one level"),
+            TimerWheelError::ZeroSlots(level) => write!(f, "level {} has no slots", level),
+            TimerWheelError::Overflow => f.write_str("timer wheel span overflows u64"),
         }
-        None
     }
+}
 
-    fn get_mut_timer(&mut self, timer_id: &u64) -> Option<&mut T> {
-        for i in 0..self.num as usize {
-            let mut found_idx = None;
-            let v = &mut self.slots[i];
-            for (idx, val) in v.iter().enumerate() {
-                if &val.id == timer_id {
-                    found_idx = Some(idx);
-                    break;
-                }
-            }
-            if let Some(idx) = found_idx {
-                return Some(&mut self.slots[i][idx].val);
-            }
-        }
-        None
+impl Error for TimerWheelError {}
+
+/// 时轮的构建器, 定时器的时间单位为毫秒, 每层时轮的刻度由下层自动计算
+///
+/// # Examples
+///
+/// ```
+/// use algorithm::TimerWheel;
+/// use std::time::Duration;
+/// fn main() {
+///     let mut timer = TimerWheel::builder()
+///         .tick(Duration::from_millis(10))
+///         .levels([100, 60, 60])
+///         .build()
+///         .unwrap();
+///     timer.add_timer(25u64);
+///     assert!(timer.update_deltatime(20).unwrap_or_default().is_empty());
+///     assert_eq!(timer.update_deltatime(10).unwrap(), vec![(1, 25)]);
+///     assert!(TimerWheel::<u64>::builder().levels([60, 0]).build().is_err());
+/// }
+/// ```
+pub struct TimerWheelBuilder<T: Timer> {
+    tick: Duration,
+    levels: Vec<u64>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Timer> TimerWheelBuilder<T> {
+    /// 每个刻度的时间, 即定时器的精度, 默认为1毫秒
+    pub fn tick(mut self, tick: Duration) -> Self {
+        self.tick = tick;
+        self
     }
 
-    fn add_step_timer(&mut self, entry: Entry<T>) {
-        let offset = entry.when % self.num;
-        self.add_timer_with_offset(entry, offset);
+    /// 从小到大每层时轮的槽位数, 如`[1000, 60, 60]`表示毫秒轮, 秒轮, 分轮
+    pub fn levels<I: IntoIterator<Item = u64>>(mut self, levels: I) -> Self {
+        self.levels = levels.into_iter().collect();
+        self
     }
 
-    fn add_timer_with_offset(&mut self, entry: Entry<T>, offset: u64) {
-        if offset > self.capation {
-            let index = (self.index + self.num - 1) % self.num;
-            self.slots[index as usize].push(entry);
-        } else if offset <= self.fix_step && !self.child.is_null() {
-            unsafe {
-                (*self.child).add_timer_with_offset(entry, offset);
-            }
-        } else {
-            // 当前偏差值还在自己的容纳范围之前，做容错，排在最后处理位
-            let index = (offset - 1) / self.fix_step;
-            let index = (index + self.index) % self.num;
-            self.slots[index as usize].push(entry);
-        }
-    }
-
-    pub fn update_index(
-        &mut self,
-        offset: u64,
-        remainder: u64,
-        result: &mut Vec<(u64, T)>,
-    ) -> (u64, u64) {
-        let next = self.index + offset;
-        let mut all = 0;
-        for idx in self.index..next {
-            if all > self.num {
-                break;
-            }
-            all += 1;
-            let idx = idx % self.num;
-            let list = &mut self.slots[idx as usize];
-            for val in list.drain(..) {
-                result.push((val.id, val.val));
-            }
+    pub fn build(self) -> Result<TimerWheel<T>, TimerWheelError> {
+        let millis = self.tick.as_millis();
+        if millis == 0 || millis * 1_000_000 != self.tick.as_nanos() || millis > u64::MAX as u128 {
+            return Err(TimerWheelError::InvalidTick);
         }
-        self.index = next % self.num;
-        if !self.child.is_null() {
-            unsafe {
-                let list = &mut self.slots[self.index as usize];
-                for mut val in list.drain(..) {
-                    val.when = (val.when % self.step).saturating_sub(remainder);
-                    if val.when == 0 {
-                        result.push((val.id, val.val));
-                    } else {
-                        (*self.child).add_step_timer(val);
-                    }
-                }
+        if self.levels.is_empty() {
+            return Err(TimerWheelError::EmptyLevels);
+        }
+        let mut span = 1u64;
+        for (i, slots) in self.levels.iter().enumerate() {
+            if *slots == 0 {
+                return Err(TimerWheelError::ZeroSlots(i));
             }
+            span = span.checked_mul(*slots).ok_or(TimerWheelError::Overflow)?;
         }
-        (next / self.num, next % self.num + remainder)
+        span.checked_mul(millis as u64)
+            .ok_or(TimerWheelError::Overflow)?;
+
+        let mut timer = TimerWheel::new();
+        timer.set_one_step(millis as u64);
+        for (i, slots) in self.levels.into_iter().enumerate() {
+            timer.append_timer_wheel(slots, LEVEL_NAMES.get(i).copied().unwrap_or("Wheel"));
+        }
+        Ok(timer)
     }
 }
 
@@ -183,9 +244,11 @@ impl<T: Timer> OneTimerWheel<T> {
 ///
 /// 每个格子代表一段时间，这段时间越短，定时器的精度就越高。
 ///
-/// 每个格子用一个Vec存储放在该格子上的延时任务。
+/// 每个格子用侵入式的双向链表存储放在该格子上的延时任务，并以定时器id建立索引，
+///
+/// 删除、获取定时器的时间复杂度均为O(1)。
 ///
-/// Mark: 在Rust中双向链表中暂未提供元素关键列表的接口，这里改用Vec，删除时会额外移动Vec值
+/// 定时器以到期的绝对刻度存放，超出最大轮范围的定时器放在溢出链表中，等可容纳时再放入时轮。
 ///
 /// # Examples
 ///
@@ -218,10 +281,12 @@ impl<T: Timer> OneTimerWheel<T> {
 /// }
 /// ```
 pub struct TimerWheel<T: Timer> {
-    /// 时轮的最大轮，以时钟为例就是时针
-    greatest: *mut OneTimerWheel<T>,
-    /// 时轮的最小轮，以时钟为例就是秒针
-    lessest: *mut OneTimerWheel<T>,
+    /// 从小到大的各层时轮，以时钟为例依次为秒针，分针，时针
+    wheels: Vec<OneTimerWheel>,
+    /// 超出最大轮范围的定时器
+    overflow: Slot,
+    /// 已到期但还未取出的定时器
+    ready: Slot,
     /// 时轮的最小间隔，以时间为例就是秒
     one_step: u64,
     /// 维护定时器id
@@ -232,8 +297,10 @@ pub struct TimerWheel<T: Timer> {
     delay_id: u64,
     /// 总共的递进步长，缓存优化触发
     all_deltatime: u64,
-    /// 当时时轮里的元素个数
-    len: usize,
+    /// 时轮已经递进的刻度数
+    cur_tick: u64,
+    /// 定时器id到节点的索引，节点记录所在的轮及槽位
+    entries: HashMap<u64, Entry<T>>,
 }
 
 impl<T: Timer> TimerWheel<T> {
@@ -249,14 +316,25 @@ impl<T: Timer> TimerWheel<T> {
     /// ```
     pub fn new() -> Self {
         Self {
-            greatest: ptr::null_mut(),
-            lessest: ptr::null_mut(),
+            wheels: vec![],
+            overflow: Slot::default(),
+            ready: Slot::default(),
             next_timer_id: 1,
             max_timer_id: u64::MAX,
             delay_id: 0,
             one_step: 1,
             all_deltatime: 0,
-            len: 0,
+            cur_tick: 0,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// 以构建器创建时轮, 无需手动计算每层的刻度
+    pub fn builder() -> TimerWheelBuilder<T> {
+        TimerWheelBuilder {
+            tick: Duration::from_millis(1),
+            levels: vec![],
+            _marker: PhantomData,
         }
     }
 
@@ -278,7 +356,7 @@ impl<T: Timer> TimerWheel<T> {
     /// }
     /// ```
     pub fn len(&self) -> usize {
-        self.len
+        self.entries.len()
     }
 
     /// 是否为空
@@ -292,7 +370,7 @@ impl<T: Timer> TimerWheel<T> {
     /// }
     /// ```
     pub fn is_empty(&self) -> bool {
-        self.len == 0
+        self.entries.is_empty()
     }
 
     /// 清除所有的槽位
@@ -312,14 +390,12 @@ impl<T: Timer> TimerWheel<T> {
     /// }
     /// ```
     pub fn clear(&mut self) {
-        let mut wheel = self.lessest;
-        while !wheel.is_null() {
-            unsafe {
-                (*wheel).clear();
-                wheel = (*wheel).parent;
-            }
+        for wheel in self.wheels.iter_mut() {
+            wheel.clear();
         }
-        self.len = 0;
+        self.overflow = Slot::default();
+        self.ready = Slot::default();
+        self.entries.clear();
     }
 
     pub fn get_one_step(&self) -> u64 {
@@ -343,32 +419,12 @@ impl<T: Timer> TimerWheel<T> {
     ///     timer.add_timer(30);
     /// }
     pub fn append_timer_wheel(&mut self, slots: u64, name: &'static str) {
-        debug_assert!(self.len == 0, "必须时轮为空才可改变时轮");
-        let step = if self.greatest.is_null() {
-            self.one_step
-        } else {
-            let mut now_step = 1;
-            let mut node = self.greatest;
-            unsafe {
-                while !node.is_null() {
-                    now_step *= (*node).capation;
-                    node = (*node).child;
-                }
-            }
-            now_step / self.one_step
-        };
-        let one = Box::into_raw(Box::new(OneTimerWheel::new(slots, step, self.one_step, name)));
-        self.delay_id = self.delay_id.max(slots * step);
-        if self.lessest.is_null() {
-            self.lessest = one;
-            self.greatest = one;
-        } else {
-            unsafe {
-                let child = self.greatest;
-                (*one).append(child);
-                self.greatest = one;
-            }
-        }
+        debug_assert!(self.entries.is_empty(), "必须时轮为空才可改变时轮");
+        debug_assert!(slots > 0, "槽位数必须大于0");
+        // 每个槽位的刻度数为所有下层轮的槽位数乘积
+        let step = self.wheels.iter().map(|w| w.num).product::<u64>();
+        self.wheels.push(OneTimerWheel::new(slots, step, name));
+        self.delay_id = self.delay_id.max(slots.saturating_mul(step));
     }
 
     /// 计时器轮的递进时间
@@ -386,10 +442,12 @@ impl<T: Timer> TimerWheel<T> {
     /// }
     pub fn update_deltatime(&mut self, delta: u64) -> Option<Vec<(u64, T)>> {
         debug_assert!(self.one_step > 0);
-        self.update_now(self.all_deltatime.wrapping_add(delta))
+        self.all_deltatime = self.all_deltatime.wrapping_add(delta);
+        self.collect_expired()
     }
 
-    /// 计时器轮的递进时间
+    /// 计时器轮的递进时间, `now`为距离上次递进到的刻度所经过的时间, 会替换之前累计的不足一个刻度的时间,
+    /// 以创建时轮后的时间线递进见[`TimerWheel::advance_to`]
     ///
     /// # Examples
     ///
@@ -399,35 +457,295 @@ impl<T: Timer> TimerWheel<T> {
     ///     let mut timer = TimerWheel::new();
     ///     timer.append_timer_wheel(60, "SecondWheel");
     ///     timer.add_timer(30);
-    ///     let val = timer.update_deltatime(30).unwrap();
+    ///     let val = timer.update_now(30).unwrap();
     ///     assert_eq!(val, vec![(1, 30)]);
     /// }
     pub fn update_now(&mut self, now: u64) -> Option<Vec<(u64, T)>> {
         debug_assert!(self.one_step > 0);
         self.all_deltatime = now;
-        let mut offset = self.all_deltatime / self.one_step;
-        if offset < self.delay_id {
-            return None;
-        }
-
-        self.all_deltatime -= offset * self.one_step;
-        let mut remainder = 0;
-        let mut result = vec![];
-        let mut wheel = self.lessest;
-        while !wheel.is_null() {
-            unsafe {
-                (offset, remainder) = (*wheel).update_index(offset, remainder, &mut result);
-                if offset == 0 {
-                    break;
-                }
-                wheel = (*wheel).parent;
-            }
+        self.collect_expired()
+    }
+
+    /// 将时轮递进到时间线上的`now`, 即创建时轮后累计递进的时间, 与[`super::TimerRBTree::advance_to`]一致,
+    /// `now`早于当前时间时不做处理
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::TimerWheel;
+    /// fn main() {
+    ///     let mut timer = TimerWheel::new();
+    ///     timer.append_timer_wheel(60, "SecondWheel");
+    ///     timer.add_timer(30);
+    ///     assert!(timer.advance_to(20).unwrap_or_default().is_empty());
+    ///     let val = timer.advance_to(30).unwrap();
+    ///     assert_eq!(val, vec![(1, 30)]);
+    ///     assert_eq!(timer.now(), 30);
+    /// }
+    pub fn advance_to(&mut self, now: u64) -> Option<Vec<(u64, T)>> {
+        debug_assert!(self.one_step > 0);
+        self.set_now(now);
+        self.collect_expired()
+    }
+
+    /// 将时轮递进到时间线上的`now`并取出一个到期的定时器, 没有到期的定时器时返回None
+    ///
+    /// 每次调用只处理到下一个到期的定时器为止, 不分配内存, 调用者可随时停止以限制每次的工作量,
+    /// 未取出的到期定时器保留在时轮中, 下次调用时继续取出
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::TimerWheel;
+    /// fn main() {
+    ///     let mut timer = TimerWheel::new();
+    ///     timer.append_timer_wheel(60, "SecondWheel");
+    ///     for v in [10u64, 20, 30, 40] {
+    ///         timer.add_timer(v);
+    ///     }
+    ///     assert_eq!(timer.poll_expired(5), None);
+    ///     assert_eq!(timer.poll_expired(30), Some((1, 10)));
+    ///     assert_eq!(timer.poll_expired(30), Some((2, 20)));
+    ///     // 剩余的留待下次取出
+    ///     let rest: Vec<_> = timer.drain_expired(50).collect();
+    ///     assert_eq!(rest, vec![(3, 30), (4, 40)]);
+    /// }
+    /// ```
+    pub fn poll_expired(&mut self, now: u64) -> Option<(u64, T)> {
+        debug_assert!(self.one_step > 0);
+        self.set_now(now);
+        self.poll()
+    }
+
+    /// 将时轮递进到时间线上的`now`, 返回逐个取出到期定时器的迭代器, 见[`TimerWheel::poll_expired`]
+    pub fn drain_expired(&mut self, now: u64) -> DrainExpired<'_, Self, T> {
+        DrainExpired::new(self, now)
+    }
+
+    /// 当前在时间线上的时间, 即创建时轮后累计递进的时间, 包括还不足一个刻度的时间
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::TimerWheel;
+    /// fn main() {
+    ///     let mut timer = TimerWheel::<u64>::new();
+    ///     timer.append_timer_wheel(60, "SecondWheel");
+    ///     timer.update_deltatime(10);
+    ///     timer.update_deltatime(5);
+    ///     assert_eq!(timer.now(), 15);
+    /// }
+    /// ```
+    pub fn now(&self) -> u64 {
+        self.cur_tick
+            .saturating_mul(self.one_step)
+            .saturating_add(self.all_deltatime)
+    }
+
+    fn set_now(&mut self, now: u64) {
+        let passed = self.cur_tick.saturating_mul(self.one_step);
+        self.all_deltatime = self.all_deltatime.max(now.saturating_sub(passed));
+    }
+
+    fn collect_expired(&mut self) -> Option<Vec<(u64, T)>> {
+        let first = self.poll()?;
+        let mut result = vec![first];
+        while let Some(val) = self.poll() {
+            result.push(val);
         }
-        self.calc_delay_id();
-        self.len -= result.len();
         Some(result)
     }
 
+    /// 取出一个到期的定时器, 必要时递进到下一个有事件的刻度, 跳过中间的空槽位
+    fn poll(&mut self) -> Option<(u64, T)> {
+        loop {
+            if let Some(timer_id) = self.ready.pop_front(&mut self.entries) {
+                return Some(self.fire(timer_id));
+            }
+            let offset = self.all_deltatime / self.one_step;
+            if offset < self.delay_id {
+                return None;
+            }
+            let target = self.cur_tick.saturating_add(offset);
+            let next = self.next_event_tick();
+            if next > target {
+                self.all_deltatime -= offset * self.one_step;
+                self.cur_tick = target;
+                self.calc_delay_id();
+                return None;
+            }
+            self.all_deltatime -= (next - self.cur_tick) * self.one_step;
+            self.cur_tick = next;
+            // 刻度已变化, 下次需要重新查找事件
+            self.delay_id = 0;
+            self.process_tick();
+        }
+    }
+
+    /// 移除到期的定时器, 周期定时器则重新加入
+    fn fire(&mut self, timer_id: u64) -> (u64, T) {
+        let mut entry = self.entries.remove(&timer_id).expect("timer entry");
+        let again = entry.repeat.as_mut().is_some_and(|r| r.fire());
+        match entry.repeat.as_ref().filter(|_| again) {
+            Some(repeat) => {
+                // 以上次的到期时间为基准重新加入, 避免误差累积, 落后太多时每次更新最多触发一次
+                let target = self.cur_tick + self.all_deltatime / self.one_step;
+                let val = (repeat.clone)(&entry.val);
+                let deadline = match repeat.period {
+                    Some(period) => entry.deadline.saturating_add(self.to_ticks(period)),
+                    None => {
+                        let when = entry.val.when_mut();
+                        self.cur_tick
+                            .saturating_add(self.to_ticks(when.saturating_add(self.all_deltatime)))
+                    }
+                };
+                self.insert_at(entry, deadline.max(target + 1));
+                (timer_id, val)
+            }
+            None => (timer_id, entry.val),
+        }
+    }
+
+    /// 处理当前刻度: 先将到达的上层槽位降级到下层, 再将最底层到期的元素移入待取出的链表
+    fn process_tick(&mut self) {
+        let now = self.cur_tick;
+        let top = self.wheels.len() - 1;
+        if !self.overflow.is_empty() && now.is_multiple_of(self.wheels[top].step) {
+            // 仍然放不下的会重新放回溢出链表
+            let mut node = std::mem::take(&mut self.overflow).head;
+            while let Some(timer_id) = node {
+                node = self.entries[&timer_id].next;
+                self.place(timer_id);
+            }
+        }
+        for level in (1..self.wheels.len()).rev() {
+            let wheel = &mut self.wheels[level];
+            if !now.is_multiple_of(wheel.step) {
+                continue;
+            }
+            let index = wheel.slot_index(now);
+            let mut node = std::mem::take(&mut wheel.slots[index]).head;
+            while let Some(timer_id) = node {
+                node = self.entries[&timer_id].next;
+                self.place(timer_id);
+            }
+        }
+        let wheel = &mut self.wheels[0];
+        let index = wheel.slot_index(now);
+        let mut node = std::mem::take(&mut wheel.slots[index]).head;
+        while let Some(timer_id) = node {
+            let entry = self.entries.get_mut(&timer_id).expect("timer entry");
+            node = entry.next;
+            entry.level = READY;
+            self.ready.push(&mut self.entries, timer_id);
+        }
+    }
+
+    /// 根据到期时序放入能容纳的最小一层时轮, 都不能容纳则放入溢出链表
+    fn place(&mut self, timer_id: u64) {
+        let now = self.cur_tick;
+        let deadline = self.entries[&timer_id].deadline;
+        let (level, slot) = match self.wheels.iter().position(|w| w.contains(deadline, now)) {
+            Some(level) => (level, self.wheels[level].slot_index(deadline)),
+            None => (OVERFLOW, 0),
+        };
+        {
+            let entry = self.entries.get_mut(&timer_id).expect("timer entry");
+            entry.level = level;
+            entry.slot = slot;
+        }
+        if level == OVERFLOW {
+            self.overflow.push(&mut self.entries, timer_id);
+        } else {
+            self.wheels[level].slots[slot].push(&mut self.entries, timer_id);
+        }
+    }
+
+    /// 最早到期的定时器的到期时序, 每层时轮的最小值必定在第一个非空槽位中
+    fn first_deadline(&self) -> Option<u64> {
+        let now = self.cur_tick;
+        let mut first: Option<u64> = None;
+        for wheel in self.wheels.iter() {
+            let start = match wheel.next_event(now) {
+                Some(start) => start,
+                None => continue,
+            };
+            if first.is_some_and(|f| f < start) {
+                continue;
+            }
+            let slot = &wheel.slots[wheel.slot_index(start)];
+            first = self.slot_min_deadline(slot, first);
+        }
+        let first = self.slot_min_deadline(&self.overflow, first);
+        self.slot_min_deadline(&self.ready, first)
+    }
+
+    fn slot_min_deadline(&self, slot: &Slot, mut first: Option<u64>) -> Option<u64> {
+        let mut node = slot.head;
+        while let Some(id) = node {
+            let entry = &self.entries[&id];
+            first = Some(first.map_or(entry.deadline, |f| f.min(entry.deadline)));
+            node = entry.next;
+        }
+        first
+    }
+
+    /// 最早到期的定时器的触发时间, 以创建时轮后累计递进的时间为准, 与[`super::TimerRBTree::tick_first`]对应
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::TimerWheel;
+    /// fn main() {
+    ///     let mut timer = TimerWheel::new();
+    ///     timer.append_timer_wheel(60, "SecondWheel");
+    ///     timer.append_timer_wheel(60, "MinuteWheel");
+    ///     assert_eq!(timer.tick_first(), None);
+    ///     timer.add_timer(150);
+    ///     timer.add_timer(90);
+    ///     timer.update_deltatime(10);
+    ///     assert_eq!(timer.tick_first(), Some(90));
+    ///     assert_eq!(timer.next_delay(), Some(80));
+    /// }
+    /// ```
+    pub fn tick_first(&self) -> Option<u64> {
+        self.first_deadline()
+            .map(|deadline| deadline.saturating_mul(self.one_step))
+    }
+
+    /// 距离最早到期的定时器触发还需递进的时间, 为空时返回None
+    pub fn next_delay(&self) -> Option<u64> {
+        self.first_deadline().map(|deadline| self.remain(deadline))
+    }
+
+    /// 下一个可能有定时器到期或需要降级的刻度
+    fn next_event_tick(&self) -> u64 {
+        let now = self.cur_tick;
+        let mut next = u64::MAX;
+        for wheel in self.wheels.iter() {
+            // 上层轮的事件不会早于下一个槽位的起始时序
+            if next <= (now / wheel.step + 1).saturating_mul(wheel.step) {
+                break;
+            }
+            if let Some(tick) = wheel.next_event(now) {
+                next = next.min(tick);
+            }
+        }
+        if let Some(top) = self.wheels.last() {
+            let mut node = self.overflow.head;
+            while let Some(id) = node {
+                let entry = &self.entries[&id];
+                // 最早可以放入最大轮的时序
+                let fit = (entry.deadline / top.step)
+                    .saturating_sub(top.num - 1)
+                    .saturating_mul(top.step);
+                next = next.min(fit.max(now + 1));
+                node = entry.next;
+            }
+        }
+        next
+    }
+
     /// 计时器轮的递进时间
     ///
     /// # Examples
@@ -450,10 +768,12 @@ impl<T: Timer> TimerWheel<T> {
         F: FnMut(&mut Self, u64, T) -> Option<(u64, T)>,
     {
         debug_assert!(self.one_step > 0);
-        self.update_now_with_callback(self.all_deltatime.wrapping_add(delta), f);
+        let result = self.update_deltatime(delta);
+        self.dispatch(result, f);
     }
 
-    /// 计时器轮的递进时间
+    /// 计时器轮的递进时间, `now`的含义与[`TimerWheel::update_now`]一致, 到期的定时器交由回调处理,
+    /// 回调返回`Some`时以返回的id重新添加
     ///
     /// # Examples
     ///
@@ -464,7 +784,7 @@ impl<T: Timer> TimerWheel<T> {
     ///     timer.append_timer_wheel(60, "SecondWheel");
     ///     timer.add_timer(30);
     ///     let mut idx = 0;
-    ///     timer.update_deltatime_with_callback(30, &mut |_, _, v| {
+    ///     timer.update_now_with_callback(30, &mut |_, _, v| {
     ///         idx = v;
     ///         None
     ///     });
@@ -475,7 +795,46 @@ impl<T: Timer> TimerWheel<T> {
         F: FnMut(&mut Self, u64, T) -> Option<(u64, T)>,
     {
         debug_assert!(self.one_step > 0);
-        if let Some(result) = self.update_now(now) {
+        let result = self.update_now(now);
+        self.dispatch(result, f);
+    }
+
+    /// 将时轮递进到时间线上的`now`, 到期的定时器交由回调处理, 回调返回`Some`时以返回的id重新添加
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::TimerWheel;
+    /// fn main() {
+    ///     let mut timer = TimerWheel::new();
+    ///     timer.append_timer_wheel(60, "SecondWheel");
+    ///     timer.add_timer(30);
+    ///     let mut fired = vec![];
+    ///     timer.advance_to_with_callback(30, &mut |_, id, v| {
+    ///         fired.push(v);
+    ///         // 以同一个id在20之后再次触发
+    ///         (v == 30).then_some((id, 20))
+    ///     });
+    ///     timer.advance_to_with_callback(50, &mut |_, _, v| {
+    ///         fired.push(v);
+    ///         None
+    ///     });
+    ///     assert_eq!(fired, vec![30, 20]);
+    /// }
+    pub fn advance_to_with_callback<F>(&mut self, now: u64, f: &mut F)
+    where
+        F: FnMut(&mut Self, u64, T) -> Option<(u64, T)>,
+    {
+        debug_assert!(self.one_step > 0);
+        let result = self.advance_to(now);
+        self.dispatch(result, f);
+    }
+
+    fn dispatch<F>(&mut self, result: Option<Vec<(u64, T)>>, f: &mut F)
+    where
+        F: FnMut(&mut Self, u64, T) -> Option<(u64, T)>,
+    {
+        if let Some(result) = result {
             let mut collect_result = vec![];
             for r in result.into_iter() {
                 if let Some(v) = (*f)(self, r.0, r.1) {
@@ -488,7 +847,7 @@ impl<T: Timer> TimerWheel<T> {
         }
     }
 
-    /// 计算下一个delay_id, 根据容器的密度稀疏有关
+    /// 计算下一个delay_id, 即距离下一个非空槽位的刻度数, 根据容器的密度稀疏有关
     /// 密度高的基本为O(1)的复杂度, 最差情况为O(n)的复杂度
     /// 总刻度数以时钟为计秒轮遍历60次,分轮遍历60次,时轮遍历12次,即最高遍历132次
     ///
@@ -503,27 +862,17 @@ impl<T: Timer> TimerWheel<T> {
     ///     assert_eq!(timer.get_delay_id(), 30);
     /// }
     pub fn calc_delay_id(&mut self) {
-        let mut next_delay_id = 0;
-        let mut wheel = self.lessest;
-        'outer: while !wheel.is_null() {
-            unsafe {
-                let (step, index, cap) = ((*wheel).step, (*wheel).index, (*wheel).num);
-                for i in 0..cap {
-                    let index = (index + i) % cap;
-                    if !(*wheel).slots[index as usize].is_empty() {
-                        next_delay_id = (i + 1) * step;
-                        break 'outer;
-                    }
-                }
-                next_delay_id = cap * step;
-                wheel = (*wheel).parent;
-            }
-        }
-        self.delay_id = next_delay_id / self.one_step;
+        self.delay_id = if self.entries.is_empty() {
+            self.wheels
+                .last()
+                .map(|w| w.num.saturating_mul(w.step))
+                .unwrap_or(0)
+        } else {
+            self.next_event_tick() - self.cur_tick
+        };
     }
 
-    /// 删除指定的定时器，时间复杂度为O(n)，
-    /// 该模型删除不具备优势，需要频繁删除请选用其它时间框架
+    /// 删除指定的定时器，时间复杂度为O(1)
     ///
     /// # Examples
     ///
@@ -535,23 +884,30 @@ impl<T: Timer> TimerWheel<T> {
     ///     let t = timer.add_timer(30);
     ///     timer.del_timer(t);
     ///     assert_eq!(timer.len(), 0);
+    ///     let a = timer.add_timer(10);
+    ///     let b = timer.add_timer(10);
+    ///     let c = timer.add_timer(10);
+    ///     assert_eq!(timer.del_timer(b), Some(10));
+    ///     assert_eq!(timer.del_timer(b), None);
+    ///     let val = timer.update_deltatime(10).unwrap();
+    ///     assert_eq!(val.iter().map(|(id, _)| *id).collect::<Vec<u64>>(), vec![a, c]);
     /// }
     pub fn del_timer(&mut self, timer_id: u64) -> Option<T> {
-        let mut wheel = self.lessest;
-        while !wheel.is_null() {
-            unsafe {
-                if let Some(v) = (*wheel).del_timer(timer_id) {
-                    self.len -= 1;
-                    return Some(v);
-                }
-                wheel = (*wheel).parent;
-            }
-        }
-        None
+        self.unlink(timer_id).map(|e| e.val)
+    }
+
+    fn unlink(&mut self, timer_id: u64) -> Option<Entry<T>> {
+        let entry = self.entries.remove(&timer_id)?;
+        let slot = match entry.level {
+            OVERFLOW => &mut self.overflow,
+            READY => &mut self.ready,
+            level => &mut self.wheels[level].slots[entry.slot],
+        };
+        slot.unlink(&mut self.entries, &entry);
+        Some(entry)
     }
 
-    /// 获取指定的定时器，时间复杂度为O(n)
-    /// 该模型获取不具备优势，需要频繁获取请选用其它时间框架
+    /// 获取指定的定时器，时间复杂度为O(1)
     ///
     /// # Examples
     ///
@@ -564,20 +920,10 @@ impl<T: Timer> TimerWheel<T> {
     ///     assert_eq!(timer.get_timer(&t), Some(&30));
     /// }
     pub fn get_timer(&self, timer_id: &u64) -> Option<&T> {
-        let mut wheel = self.lessest;
-        while !wheel.is_null() {
-            unsafe {
-                if let Some(v) = (*wheel).get_timer(timer_id) {
-                    return Some(v);
-                }
-                wheel = (*wheel).parent;
-            }
-        }
-        None
+        self.entries.get(timer_id).map(|e| &e.val)
     }
 
-    /// 获取指定的定时器，时间复杂度为O(n)
-    /// 该模型获取不具备优势，需要频繁获取请选用其它时间框架
+    /// 获取指定的定时器，时间复杂度为O(1)
     ///
     /// # Examples
     ///
@@ -592,16 +938,7 @@ impl<T: Timer> TimerWheel<T> {
     ///     assert_eq!(val, vec![(1, 33)]);
     /// }
     pub fn get_mut_timer(&mut self, timer_id: &u64) -> Option<&mut T> {
-        let mut wheel = self.lessest;
-        while !wheel.is_null() {
-            unsafe {
-                if let Some(v) = (*wheel).get_mut_timer(timer_id) {
-                    return Some(v);
-                }
-                wheel = (*wheel).parent;
-            }
-        }
-        None
+        self.entries.get_mut(timer_id).map(|e| &mut e.val)
     }
 
     pub fn get_max_timerid(&self) -> u64 {
@@ -613,11 +950,18 @@ impl<T: Timer> TimerWheel<T> {
     }
 
     fn get_next_timerid(&mut self) -> u64 {
-        let timer_id = self.next_timer_id;
-        if self.next_timer_id >= self.max_timer_id {
-            self.next_timer_id = 1;
-        } else {
-            self.next_timer_id = self.next_timer_id + 1;
+        let mut timer_id;
+        loop {
+            timer_id = self.next_timer_id;
+            if self.next_timer_id >= self.max_timer_id {
+                self.next_timer_id = 1;
+            } else {
+                self.next_timer_id += 1;
+            }
+
+            if !self.entries.contains_key(&timer_id) {
+                break;
+            }
         }
         timer_id
     }
@@ -633,23 +977,323 @@ impl<T: Timer> TimerWheel<T> {
     ///     timer.add_timer(30);
     /// }
     pub fn add_timer(&mut self, val: T) -> u64 {
-        debug_assert!(!self.greatest.is_null(), "必须设置时轮才能添加元素");
+        debug_assert!(!self.wheels.is_empty(), "必须设置时轮才能添加元素");
         let timer_id: u64 = self.get_next_timerid();
         self.add_timer_by_id(timer_id, val);
         timer_id
     }
 
+    /// 以指定的id添加定时器元素, 若该id已存在则替换原有的定时器
     pub fn add_timer_by_id(&mut self, timer_id: u64, mut val: T) {
-        let entry = Entry {
-            when: val.when_mut().max(1),
-            val,
-            id: timer_id,
+        debug_assert!(!self.wheels.is_empty(), "必须设置时轮才能添加元素");
+        self.del_timer(timer_id);
+        let when = val.when_mut();
+        self.insert_entry(Entry::new(timer_id, val), when);
+    }
+
+    /// 添加在时间线上`at`时触发的定时器, 不使用值的[`Timer::when`], `at`不晚于当前时间时在下一个刻度触发
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::TimerWheel;
+    /// fn main() {
+    ///     let mut timer = TimerWheel::new();
+    ///     timer.append_timer_wheel(60, "SecondWheel");
+    ///     timer.update_deltatime(10);
+    ///     let t = timer.add_timer_at(0u64, 25);
+    ///     assert_eq!(timer.tick_first(), Some(25));
+    ///     assert_eq!(timer.advance_to(25).unwrap(), vec![(t, 0)]);
+    /// }
+    /// ```
+    pub fn add_timer_at(&mut self, val: T, at: u64) -> u64 {
+        debug_assert!(!self.wheels.is_empty(), "必须设置时轮才能添加元素");
+        let timer_id = self.get_next_timerid();
+        self.add_timer_by_id_at(timer_id, val, at);
+        timer_id
+    }
+
+    /// 以指定的id添加在时间线上`at`时触发的定时器, 若该id已存在则替换原有的定时器
+    pub fn add_timer_by_id_at(&mut self, timer_id: u64, val: T, at: u64) {
+        debug_assert!(!self.wheels.is_empty(), "必须设置时轮才能添加元素");
+        self.del_timer(timer_id);
+        let when = at.saturating_sub(self.now());
+        self.insert_entry(Entry::new(timer_id, val), when);
+    }
+
+    /// 添加周期定时器, 每隔`period`触发一次, 触发后以同一个id自动重新加入,
+    /// 通过`del_timer`可取消整个周期, 首次触发在`period`之后
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::TimerWheel;
+    /// fn main() {
+    ///     let mut timer = TimerWheel::new();
+    ///     timer.append_timer_wheel(60, "SecondWheel");
+    ///     let t = timer.add_interval(7u64, 10);
+    ///     assert_eq!(timer.update_deltatime(10).unwrap(), vec![(t, 7)]);
+    ///     assert_eq!(timer.update_deltatime(10).unwrap(), vec![(t, 7)]);
+    ///     assert_eq!(timer.len(), 1);
+    ///     assert_eq!(timer.del_timer(t), Some(7));
+    ///     assert!(timer.is_empty());
+    /// }
+    /// ```
+    pub fn add_interval(&mut self, val: T, period: u64) -> u64
+    where
+        T: Clone,
+    {
+        self.insert_repeat(val, Repeat::new(period, None))
+    }
+
+    /// 添加最多触发`times`次的周期定时器, 最后一次触发后自动移除
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::TimerWheel;
+    /// fn main() {
+    ///     let mut timer = TimerWheel::new();
+    ///     timer.append_timer_wheel(60, "SecondWheel");
+    ///     let t = timer.add_interval_times(1, 5, 2);
+    ///     assert_eq!(timer.update_deltatime(5).unwrap(), vec![(t, 1)]);
+    ///     assert_eq!(timer.update_deltatime(5).unwrap(), vec![(t, 1)]);
+    ///     assert!(timer.is_empty());
+    /// }
+    /// ```
+    pub fn add_interval_times(&mut self, val: T, period: u64, times: u64) -> u64
+    where
+        T: Clone,
+    {
+        self.insert_repeat(val, Repeat::new(period, Some(times)))
+    }
+
+    /// 添加自动重复的定时器, 每次触发后以值的[`Timer::when_mut`]重新计算下次的延时并以同一个id重新加入,
+    /// 适用于触发间隔不固定的定时器, 如[`super::CronTimer`], 通过`del_timer`可取消
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "sim")]
+    /// use algorithm::{CronTimer, SimClock, TimerWheel};
+    /// use std::time::Duration;
+    /// # #[cfg(feature = "sim")]
+    /// fn main() {
+    ///     let mut wheel = TimerWheel::builder()
+    ///         .tick(Duration::from_secs(1))
+    ///         .levels([60, 60, 24])
+    ///         .build()
+    ///         .unwrap();
+    ///     // 2024-01-01 00:00:00 UTC
+    ///     let start = 1_704_067_200_000;
+    ///     let mut clock = SimClock::new(start);
+    ///     let cron = CronTimer::new("quarter", "*/15 * * * *".parse().unwrap());
+    ///     let t = wheel.add_repeat(cron);
+    ///     let wheel = clock.attach("cron", wheel);
+    ///     assert_eq!(clock.run(3600 * 1000, 1000), 4);
+    ///     let fired: Vec<_> = wheel.events().iter().map(|e| (e.timer_id, e.value.val, (e.at - start) / 60_000)).collect();
+    ///     assert_eq!(fired, vec![(t, "quarter", 15), (t, "quarter", 30), (t, "quarter", 45), (t, "quarter", 60)]);
+    /// }
+    /// # #[cfg(not(feature = "sim"))]
+    /// # fn main() {}
+    /// ```
+    pub fn add_repeat(&mut self, val: T) -> u64
+    where
+        T: Clone,
+    {
+        self.insert_repeat(val, Repeat::by_when())
+    }
+
+    fn insert_repeat(&mut self, mut val: T, repeat: Repeat<T>) -> u64 {
+        debug_assert!(!self.wheels.is_empty(), "必须设置时轮才能添加元素");
+        let timer_id = self.get_next_timerid();
+        let when = match repeat.period {
+            Some(period) => period,
+            None => val.when_mut(),
         };
-        self.delay_id = self.delay_id.min(entry.when / self.one_step);
-        unsafe {
-            (*self.greatest).add_timer(entry);
+        let mut entry = Entry::new(timer_id, val);
+        entry.repeat = Some(repeat);
+        self.insert_entry(entry, when);
+        timer_id
+    }
+
+    /// 将时间转换成刻度数, 不足一个刻度的按一个刻度计算
+    fn to_ticks(&self, when: u64) -> u64 {
+        when.div_ceil(self.one_step).max(1)
+    }
+
+    /// 以当前时间加上`when`作为到期时间加入, 包括已累计但还未递进的时间
+    fn insert_entry(&mut self, entry: Entry<T>, when: u64) {
+        let ticks = self.to_ticks(when.saturating_add(self.all_deltatime));
+        self.insert_at(entry, self.cur_tick.saturating_add(ticks));
+    }
+
+    fn insert_at(&mut self, mut entry: Entry<T>, deadline: u64) {
+        entry.deadline = deadline;
+        self.delay_id = self.delay_id.min(deadline - self.cur_tick);
+        let timer_id = entry.id;
+        self.entries.insert(timer_id, entry);
+        self.place(timer_id);
+    }
+
+    /// 将定时器的触发时间重新设置为当前时间之后的`when`, 保留原有的id及值, 时间复杂度为O(1)
+    /// 返回false表示该定时器不存在
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::TimerWheel;
+    /// fn main() {
+    ///     let mut timer = TimerWheel::new();
+    ///     timer.append_timer_wheel(60, "SecondWheel");
+    ///     let t = timer.add_timer(10);
+    ///     assert!(timer.reset_timer(t, 30));
+    ///     assert!(timer.update_deltatime(10).unwrap_or_default().is_empty());
+    ///     let val = timer.update_deltatime(20).unwrap();
+    ///     assert_eq!(val, vec![(t, 10)]);
+    ///     assert!(!timer.reset_timer(t, 30));
+    /// }
+    /// ```
+    pub fn reset_timer(&mut self, timer_id: u64, when: u64) -> bool {
+        match self.unlink(timer_id) {
+            Some(entry) => {
+                self.insert_entry(entry, when);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 将定时器的触发时间重新设置为时间线上的`at`, 保留原有的id及值
+    /// 返回false表示该定时器不存在
+    pub fn reset_timer_at(&mut self, timer_id: u64, at: u64) -> bool {
+        let when = at.saturating_sub(self.now());
+        self.reset_timer(timer_id, when)
+    }
+
+    /// 将定时器的触发时间在剩余时间的基础上延后`delta`, 保留原有的id及值
+    /// 返回false表示该定时器不存在
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::TimerWheel;
+    /// fn main() {
+    ///     let mut timer = TimerWheel::new();
+    ///     timer.append_timer_wheel(60, "SecondWheel");
+    ///     let t = timer.add_timer(10);
+    ///     timer.add_timer(5);
+    ///     assert_eq!(timer.update_deltatime(5).unwrap(), vec![(2, 5)]);
+    ///     assert!(timer.postpone(t, 10));
+    ///     assert!(timer.update_deltatime(10).unwrap_or_default().is_empty());
+    ///     assert_eq!(timer.update_deltatime(5).unwrap(), vec![(t, 10)]);
+    /// }
+    /// ```
+    pub fn postpone(&mut self, timer_id: u64, delta: u64) -> bool {
+        match self.unlink(timer_id) {
+            Some(entry) => {
+                let remain = self.remain(entry.deadline);
+                self.insert_entry(entry, remain.saturating_add(delta));
+                true
+            }
+            None => false,
         }
-        self.len += 1;
+    }
+
+    /// 距离到期时序还需递进的时间
+    fn remain(&self, deadline: u64) -> u64 {
+        (deadline.saturating_sub(self.cur_tick) * self.one_step).saturating_sub(self.all_deltatime)
+    }
+
+    /// 导出所有待触发的定时器, 按剩余时间排序, 可在重启后通过[`TimerWheel::restore_timers`]恢复,
+    /// 周期定时器同时导出重复周期及剩余的触发次数
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::TimerWheel;
+    /// fn main() {
+    ///     let mut timer = TimerWheel::new();
+    ///     timer.append_timer_wheel(60, "SecondWheel");
+    ///     timer.add_timer(30u64);
+    ///     let t = timer.add_timer(50u64);
+    ///     timer.update_deltatime(20);
+    ///     let pending = timer.export_timers();
+    ///     let remain: Vec<_> = pending.iter().map(|p| (p.timer_id, p.remain)).collect();
+    ///     assert_eq!(remain, vec![(1, 10), (t, 30)]);
+    ///
+    ///     let mut restored = TimerWheel::new();
+    ///     restored.append_timer_wheel(60, "SecondWheel");
+    ///     restored.restore_timers(pending).unwrap();
+    ///     assert_eq!(restored.update_deltatime(10).unwrap(), vec![(1, 30)]);
+    ///     // 新分配的id在恢复的id之后
+    ///     assert_eq!(restored.add_timer(5), t + 1);
+    ///     assert_eq!(restored.update_deltatime(20).unwrap(), vec![(t + 1, 5), (t, 50)]);
+    ///
+    ///     // 周期定时器恢复后继续重复
+    ///     let mut timer = TimerWheel::new();
+    ///     timer.append_timer_wheel(60, "SecondWheel");
+    ///     let t = timer.add_interval_times(7u64, 10, 3);
+    ///     timer.update_deltatime(10);
+    ///     let mut restored = TimerWheel::new();
+    ///     restored.append_timer_wheel(60, "SecondWheel");
+    ///     restored.restore_timers(timer.export_timers()).unwrap();
+    ///     assert_eq!(restored.update_deltatime(10).unwrap(), vec![(t, 7)]);
+    ///     assert_eq!(restored.update_deltatime(10).unwrap(), vec![(t, 7)]);
+    ///     assert!(restored.is_empty());
+    /// }
+    /// ```
+    pub fn export_timers(&self) -> Vec<PendingTimer<T>>
+    where
+        T: Clone,
+    {
+        let mut pending: Vec<_> = self
+            .entries
+            .values()
+            .map(|entry| PendingTimer {
+                timer_id: entry.id,
+                remain: self.remain(entry.deadline),
+                val: entry.val.clone(),
+                repeat: entry.repeat.as_ref().map(Repeat::export),
+            })
+            .collect();
+        pending.sort_by_key(|p| (p.remain, p.timer_id));
+        pending
+    }
+
+    /// 恢复导出的定时器, 保留原有的id, 以剩余时间重新加入, 该id已存在时替换原有的定时器,
+    /// 周期定时器恢复后以导出的周期继续重复
+    ///
+    /// 所有的id须在`1..=max_timer_id`的范围内, 否则不做任何修改并返回超出范围的id
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use algorithm::{PendingTimer, TimerWheel};
+    /// fn main() {
+    ///     let mut timer = TimerWheel::new();
+    ///     timer.append_timer_wheel(60, "SecondWheel");
+    ///     timer.set_max_timerid(100);
+    ///     let pending = vec![PendingTimer { timer_id: 101, remain: 5, val: 5u64, repeat: None }];
+    ///     assert_eq!(timer.restore_timers(pending), Err(101));
+    ///     assert!(timer.is_empty());
+    /// }
+    /// ```
+    pub fn restore_timers<I>(&mut self, timers: I) -> Result<(), u64>
+    where
+        T: Clone,
+        I: IntoIterator<Item = PendingTimer<T>>,
+    {
+        debug_assert!(!self.wheels.is_empty(), "必须设置时轮才能添加元素");
+        let timers: Vec<_> = timers.into_iter().collect();
+        self.next_timer_id = restore_next_id(&timers, self.next_timer_id, self.max_timer_id)?;
+        for p in timers {
+            self.del_timer(p.timer_id);
+            let mut entry = Entry::new(p.timer_id, p.val);
+            entry.repeat = p.repeat.map(Repeat::restore);
+            self.insert_entry(entry, p.remain);
+        }
+        Ok(())
     }
 
     /// 获取下一个延时
@@ -671,30 +1315,101 @@ impl<T: Timer> TimerWheel<T> {
 impl<T: Timer> Display for TimerWheel<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str("TimerWheel {\r\n")?;
-        let mut wheel = self.greatest;
-        while !wheel.is_null() {
-            unsafe {
-                f.write_fmt(format_args!(
-                    "{}, slots: {}, step: {}",
-                    (*wheel).name,
-                    (*wheel).slots.len(),
-                    (*wheel).step
-                ))?;
-                wheel = (*wheel).child;
-            }
+        for wheel in self.wheels.iter().rev() {
+            f.write_fmt(format_args!(
+                "{}, slots: {}, step: {}",
+                wheel.name,
+                wheel.slots.len(),
+                wheel.step * self.one_step
+            ))?;
         }
         f.write_str("}")
     }
 }
 
-impl<T: Timer> Drop for TimerWheel<T> {
-    fn drop(&mut self) {
-        let mut wheel = self.greatest;
-        while !wheel.is_null() {
-            unsafe {
-                let val = *Box::from_raw(wheel);
-                wheel = val.child;
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::TimerWheel;
+
+    #[test]
+    fn test_fire_on_time() {
+        let mut rng = rand::rng();
+        let mut timer = TimerWheel::builder().levels([16, 8, 4]).build().unwrap();
+        let mut now = 0u64;
+        let mut pending = vec![];
+        for _ in 0..2000 {
+            if rng.random_bool(0.3) {
+                // 包含超出时轮范围的定时器
+                let delay = rng.random_range(1..2000u64);
+                let id = timer.add_timer(delay);
+                pending.push((id, now + delay));
+            }
+            if rng.random_bool(0.05) && !pending.is_empty() {
+                let (id, _) = pending.swap_remove(rng.random_range(0..pending.len()));
+                assert!(timer.del_timer(id).is_some());
             }
+            now += 1;
+            for (id, _) in timer.update_deltatime(1).unwrap_or_default() {
+                let pos = pending.iter().position(|(i, _)| *i == id).unwrap();
+                assert_eq!(pending.swap_remove(pos).1, now);
+            }
+            assert!(pending.iter().all(|(_, when)| *when > now));
+            assert_eq!(timer.len(), pending.len());
+            let first = pending.iter().map(|(_, when)| *when).min();
+            assert_eq!(timer.tick_first(), first);
+            assert_eq!(timer.next_delay(), first.map(|when| when - now));
+        }
+    }
+
+    #[test]
+    fn test_drain_bounded() {
+        let mut timer = TimerWheel::builder().levels([16, 8]).build().unwrap();
+        for v in 1..=100u64 {
+            timer.add_timer(v);
         }
+        let t = timer.add_interval(0u64, 3);
+        let batch: Vec<u64> = timer.drain_expired(100).take(8).map(|(_, v)| v).collect();
+        assert_eq!(batch, vec![1, 2, 3, 0, 4, 5, 6, 7]);
+        // 剩余的留待下次取出, 周期定时器在本次更新内不再触发
+        let rest: Vec<u64> = timer.drain_expired(100).map(|(_, v)| v).collect();
+        assert_eq!(rest, (8..=100).collect::<Vec<u64>>());
+        assert_eq!(timer.next_delay(), Some(1));
+        assert_eq!(timer.poll_expired(101), Some((t, 0)));
+        assert_eq!(timer.poll_expired(101), None);
+    }
+
+    #[test]
+    fn test_export_restore() {
+        let mut timer = TimerWheel::builder().levels([10, 10]).build().unwrap();
+        for delay in [5u64, 95, 250, 1500] {
+            timer.add_timer(delay);
+        }
+        let t = timer.add_interval_times(0u64, 400, 3);
+        timer.update_deltatime(37);
+        // 恢复到不同配置的时轮中, 时间线从0开始
+        let mut restored = TimerWheel::builder().levels([16, 4]).build().unwrap();
+        restored.restore_timers(timer.export_timers()).unwrap();
+        let mut fired = vec![];
+        for now in 1..=1500 {
+            for (id, _) in restored.update_deltatime(1).unwrap_or_default() {
+                fired.push((id, now + 37));
+            }
+        }
+        assert_eq!(fired, vec![(2, 95), (3, 250), (t, 400), (t, 800), (t, 1200), (4, 1500)]);
+        assert!(restored.is_empty());
+    }
+
+    #[test]
+    fn test_overflow() {
+        let mut timer = TimerWheel::builder().levels([10, 10]).build().unwrap();
+        let far = timer.add_timer(1050u64);
+        timer.add_timer(250u64);
+        assert_eq!(timer.update_deltatime(250).unwrap(), vec![(2, 250)]);
+        assert!(timer.update_deltatime(799).unwrap_or_default().is_empty());
+        assert_eq!(timer.get_timer(&far), Some(&1050));
+        assert_eq!(timer.update_deltatime(1).unwrap(), vec![(far, 1050)]);
+        assert!(timer.is_empty());
     }
 }
@@ -1,12 +1,33 @@
-
+#[cfg(feature = "sim")]
+use std::cell::Cell;
 use std::time::SystemTime;
 
+#[cfg(feature = "sim")]
+thread_local! {
+    /// 模拟时钟设置的虚拟时间, 设置后当前线程获取的时间戳均为该值
+    static VIRTUAL_NOW: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// 设置当前线程的虚拟时间(毫秒), 返回之前的值, None表示使用真实时间
+#[cfg(feature = "sim")]
+pub(crate) fn set_virtual_milltimestamp(now: Option<u64>) -> Option<u64> {
+    VIRTUAL_NOW.with(|v| v.replace(now))
+}
+
 #[inline(always)]
 pub fn get_timestamp() -> u64 {
+    #[cfg(feature = "sim")]
+    if let Some(now) = VIRTUAL_NOW.with(|v| v.get()) {
+        return now / 1000;
+    }
     SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).expect("ok").as_secs()
 }
 
 #[inline(always)]
 pub fn get_milltimestamp() -> u64 {
+    #[cfg(feature = "sim")]
+    if let Some(now) = VIRTUAL_NOW.with(|v| v.get()) {
+        return now;
+    }
     SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).expect("ok").as_millis() as u64
-}
\ No newline at end of file
+}
@@ -0,0 +1,278 @@
+use std::{
+    cell::RefCell,
+    future::Future,
+    pin::pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+    time::Duration,
+};
+
+use algorithm::{DefaultHasher, LruCache};
+use algorithm_macro::cache;
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on<F: Future>(fut: F) -> F::Output {
+    let mut fut = pin!(fut);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(v) = fut.as_mut().poll(&mut cx) {
+            return v;
+        }
+        thread::park();
+    }
+}
+
+/// 让出一次执行权, 模拟异步io
+async fn yield_now() {
+    let mut yielded = false;
+    std::future::poll_fn(|cx| {
+        if yielded {
+            Poll::Ready(())
+        } else {
+            yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+#[cache(LruCache : LruCache::new(20))]
+fn double(u: u64) -> u64 {
+    u * 2
+}
+
+#[test]
+fn test_cache_with_poisoned() {
+    assert_eq!(double(1), 2);
+    // 在访问缓存时panic使锁中毒
+    let ret = thread::spawn(|| double_cache_with(|_| panic!("poison cache lock"))).join();
+    assert!(ret.is_err());
+    // 锁中毒后仍可访问缓存, 缓存函数跳过缓存直接计算
+    assert_eq!(double_cache_with(|c| c.get(&(1,)).copied()), Some(2));
+    assert_eq!(double(3), 6);
+    double_cache_with(|c| c.clear());
+    assert_eq!(double_cache_with(|c| c.len()), 0);
+}
+
+static TRIPLE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+#[cache(LruCache : LruCache::new(20))]
+fn triple(u: u64) -> u64 {
+    TRIPLE_COUNT.fetch_add(1, Ordering::Relaxed);
+    u * 3
+}
+
+#[test]
+fn test_companions() {
+    let sum: u64 = (0..21).map(|v| triple(v % 3)).sum();
+    assert_eq!(sum, 63);
+    assert_eq!(TRIPLE_COUNT.load(Ordering::Relaxed), 3);
+    assert_eq!(triple_cache_len(), 3);
+    assert_eq!(triple_cache_remove(1), Some(3));
+    assert_eq!(triple_cache_remove(1), None);
+    assert_eq!(triple_cache_with(|c| c.get(&(2,)).copied()), Some(6));
+    // 被移除的结果重新计算
+    assert_eq!(triple(1), 3);
+    assert_eq!(TRIPLE_COUNT.load(Ordering::Relaxed), 4);
+    triple_cache_clear();
+    assert_eq!(triple_cache_len(), 0);
+    assert_eq!(triple(2), 6);
+    assert_eq!(TRIPLE_COUNT.load(Ordering::Relaxed), 5);
+}
+
+#[cache(LruCache : LruCache::new(100))]
+#[cache_cfg(ignore_args = call_count)]
+#[cache_cfg(thread)]
+fn fib(x: u64, call_count: &mut u32) -> u64 {
+    *call_count += 1;
+    if x <= 1 {
+        1
+    } else {
+        fib(x - 1, call_count) + fib(x - 2, call_count)
+    }
+}
+
+#[test]
+fn test_ignore_args() {
+    let mut call_count = 0;
+    assert_eq!(fib(39, &mut call_count), 102_334_155);
+    // 被忽略的参数不参与缓存的key, 每个x只计算一次
+    assert_eq!(call_count, 40);
+    assert_eq!(fib_cache_len(), 40);
+    assert_eq!(fib(20, &mut call_count), 10_946);
+    assert_eq!(call_count, 40);
+}
+
+static SHARD_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+#[cache(LruCache : LruCache::new(20))]
+#[cache_cfg(shard = 8)]
+fn shard_func(u: u64) -> u64 {
+    SHARD_COUNT.fetch_add(1, Ordering::Relaxed);
+    thread::sleep(Duration::from_millis(10));
+    u * 10
+}
+
+#[test]
+fn test_shard() {
+    let handles: Vec<_> = (0..4)
+        .map(|_| thread::spawn(|| (0..21).map(|v| shard_func(v % 3)).sum::<u64>()))
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), 210);
+    }
+    // 同一个key可能被多个线程同时计算, 但缓存中只保存一份
+    assert!(SHARD_COUNT.load(Ordering::Relaxed) >= 3);
+    assert_eq!(shard_func_cache_len(), 3);
+    let mut total = 0;
+    shard_func_cache_with(|c| total += c.len());
+    assert_eq!(total, 3);
+    assert_eq!(shard_func_cache_remove(2), Some(20));
+    assert_eq!(shard_func_cache_len(), 2);
+    let count = SHARD_COUNT.load(Ordering::Relaxed);
+    assert_eq!(shard_func(0), 0);
+    assert_eq!(SHARD_COUNT.load(Ordering::Relaxed), count);
+    shard_func_cache_clear();
+    assert_eq!(shard_func_cache_len(), 0);
+}
+
+struct RefCellService {
+    base: u64,
+    calls: RefCell<usize>,
+    cache: RefCell<LruCache<(u64,), u64, DefaultHasher>>,
+}
+
+impl RefCellService {
+    #[cache]
+    #[cache_cfg(field = cache)]
+    fn method(&self, u: u64) -> u64 {
+        *self.calls.borrow_mut() += 1;
+        u * self.base
+    }
+}
+
+#[test]
+fn test_field_refcell() {
+    let service = RefCellService {
+        base: 10,
+        calls: RefCell::new(0),
+        cache: RefCell::new(LruCache::new(20)),
+    };
+    let sum: u64 = (0..21).map(|v| service.method(v % 3)).sum();
+    assert_eq!(sum, 210);
+    assert_eq!(*service.calls.borrow(), 3);
+    assert_eq!(service.cache.borrow().len(), 3);
+    // 每个实例拥有独立的缓存
+    let other = RefCellService {
+        base: 100,
+        calls: RefCell::new(0),
+        cache: RefCell::new(LruCache::new(20)),
+    };
+    assert_eq!(other.method(1), 100);
+    assert_eq!(*other.calls.borrow(), 1);
+    assert_eq!(service.method(1), 10);
+}
+
+struct MutexService {
+    base: u64,
+    calls: AtomicUsize,
+    cache: Mutex<LruCache<(u64,), u64, DefaultHasher>>,
+}
+
+impl MutexService {
+    #[cache]
+    #[cache_cfg(field = cache)]
+    #[cache_cfg(thread)]
+    fn method(&self, u: u64) -> u64 {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        u * self.base
+    }
+}
+
+#[test]
+fn test_field_mutex() {
+    let service = Arc::new(MutexService {
+        base: 10,
+        calls: AtomicUsize::new(0),
+        cache: Mutex::new(LruCache::new(20)),
+    });
+    // 先填充缓存, 之后多个线程只读取缓存
+    for v in 0..3 {
+        assert_eq!(service.method(v), v * 10);
+    }
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let service = service.clone();
+            thread::spawn(move || (0..21).map(|v| service.method(v % 3)).sum::<u64>())
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), 210);
+    }
+    assert_eq!(service.calls.load(Ordering::Relaxed), 3);
+    assert_eq!(service.cache.lock().unwrap().len(), 3);
+}
+
+static ASYNC_COUNT: AtomicUsize = AtomicUsize::new(0);
+static DEDUP_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+#[cache(LruCache : LruCache::new(20))]
+async fn slow_async(u: u64) -> u64 {
+    ASYNC_COUNT.fetch_add(1, Ordering::Relaxed);
+    yield_now().await;
+    u * 10
+}
+
+#[cache(LruCache : LruCache::new(20))]
+#[cache_cfg(dedup)]
+async fn dedup_async(u: u64) -> u64 {
+    DEDUP_COUNT.fetch_add(1, Ordering::Relaxed);
+    yield_now().await;
+    u * 10
+}
+
+#[test]
+fn test_async() {
+    let sum: u64 = (0..21).map(|v| block_on(slow_async(v % 3))).sum();
+    assert_eq!(sum, 210);
+    assert_eq!(ASYNC_COUNT.load(Ordering::Relaxed), 3);
+    assert_eq!(slow_async_cache_len(), 3);
+}
+
+#[test]
+fn test_async_dedup() {
+    // 多个相同参数的调用同时进行, 只会执行一次, 其余的等待该次的结果
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut futures: Vec<_> = (0..4).map(|_| Box::pin(dedup_async(5))).collect();
+    for fut in futures.iter_mut() {
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+    }
+    for fut in futures.iter_mut() {
+        assert_eq!(fut.as_mut().poll(&mut cx), Poll::Ready(50));
+    }
+    assert_eq!(DEDUP_COUNT.load(Ordering::Relaxed), 1);
+
+    // 多个线程同时等待同一个key
+    let handles: Vec<_> = (0..8)
+        .map(|_| thread::spawn(|| block_on(dedup_async(7))))
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), 70);
+    }
+    assert_eq!(DEDUP_COUNT.load(Ordering::Relaxed), 2);
+    assert_eq!(dedup_async_cache_len(), 2);
+}